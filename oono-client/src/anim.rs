@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+use egui::{Context, Id, LayerId, Order, Rect};
+
+use crate::{
+    card::{Card, Color},
+    card_view::{self, CardStyle, ASPECT},
+};
+
+/// Seconds a card takes to cross the table.
+const FLIGHT_SECS: f64 = 0.4;
+/// Seconds between cards headed for the same place.
+const STAGGER_SECS: f64 = 0.12;
+/// Cards never fly bigger than this.
+const MAX_HEIGHT: f32 = 90.;
+
+/// Places on the table that cards fly between.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Spot {
+    /// A card in my hand, by index.
+    Hand(usize),
+    Pot,
+    Discard,
+    /// An opponent in the side panel, by seat.
+    Opponent(usize),
+}
+
+/// The parts of an `UpdateResponse` needed to tell what changed since the last one.
+pub struct Table {
+    pub hand: Vec<Card>,
+    pub discard: Card,
+    /// Hand sizes by seat.
+    pub hand_sizes: HashMap<usize, usize>,
+}
+
+struct Flight {
+    /// `None` flies face down.
+    card: Option<Card>,
+    from: Spot,
+    to: Spot,
+    start: f64,
+}
+
+/// Cards moving across the table, worked out by comparing successive updates.
+#[derive(Default)]
+pub struct Animations {
+    flights: Vec<Flight>,
+    /// Where every spot was laid out this frame.
+    spots: HashMap<Spot, Rect>,
+    last: Option<Table>,
+}
+
+impl Animations {
+    /// Compares `table` with the last update and sends off a card for everything that moved.
+    /// `me` is my seat, `now` the egui time.
+    pub fn update(&mut self, table: Table, me: Option<usize>, now: f64) {
+        let old = match self.last.replace(table) {
+            Some(old) => old,
+            None => return,
+        };
+        let mut queued: HashMap<Spot, usize> = HashMap::new();
+        for (card, from, to) in moves(&old, self.last.as_ref().unwrap(), me) {
+            let n = queued.entry(to).or_default();
+            self.flights.push(Flight {
+                card,
+                from,
+                to,
+                start: now + *n as f64 * STAGGER_SECS,
+            });
+            *n += 1;
+        }
+    }
+
+    /// Forgets the last update, so the next one starts from scratch.
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.flights.clear();
+    }
+
+    /// Remembers where `spot` was laid out this frame.
+    pub fn place(&mut self, spot: Spot, rect: Rect) {
+        self.spots.insert(spot, rect);
+    }
+
+    /// Paints the cards in flight on top of everything else. Call once a frame, after every
+    /// spot has been placed.
+    pub fn paint(&mut self, ctx: &Context, style: CardStyle) {
+        let now = ctx.input().time;
+        self.flights.retain(|f| now < f.start + FLIGHT_SECS);
+        let painter = ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("flights")));
+        for f in &self.flights {
+            let t = ((now - f.start) / FLIGHT_SECS) as f32;
+            if t < 0. {
+                continue;
+            }
+            let (from, to) = match (self.rect(f.from), self.rect(f.to)) {
+                (Some(from), Some(to)) => (card_rect(from), card_rect(to)),
+                _ => continue,
+            };
+            let t = t * t * (3. - 2. * t);
+            let rect = Rect::from_center_size(
+                from.center() + (to.center() - from.center()) * t,
+                from.size() + (to.size() - from.size()) * t,
+            );
+            card_view::paint(&painter, rect, f.card, style);
+        }
+        self.spots.clear();
+    }
+
+    /// Where `spot` is. A card that just left my hand flies from where the end of the hand is
+    /// now, if its own slot is gone.
+    fn rect(&self, spot: Spot) -> Option<Rect> {
+        match (self.spots.get(&spot), spot) {
+            (Some(r), _) => Some(*r),
+            (None, Spot::Hand(i)) => (0..i)
+                .rev()
+                .find_map(|i| self.spots.get(&Spot::Hand(i)))
+                .copied(),
+            (None, _) => None,
+        }
+    }
+}
+
+/// A card sized rect in the middle of `spot`.
+fn card_rect(spot: Rect) -> Rect {
+    let height = spot.height().min(MAX_HEIGHT);
+    Rect::from_center_size(spot.center(), egui::vec2(height * ASPECT, height))
+}
+
+/// A wild as it sits in a hand, before a color was chosen for it.
+fn uncolored(card: Card) -> Card {
+    match card {
+        Card::Wild(_) => Card::Wild(Color::None),
+        Card::PlusFour(_) => Card::PlusFour(Color::None),
+        c => c,
+    }
+}
+
+/// Every card that moved between `old` and `new`: which card (`None` when it's face down),
+/// from where and to where.
+fn moves(old: &Table, new: &Table, me: Option<usize>) -> Vec<(Option<Card>, Spot, Spot)> {
+    let mut moves = vec![];
+    let played = old.discard != new.discard;
+
+    // match up the cards I kept, whatever is left over went or came
+    let mut added: Vec<Option<Card>> = new.hand.iter().copied().map(Some).collect();
+    let mut gone = vec![];
+    for (i, c) in old.hand.iter().enumerate() {
+        match added.iter().position(|n| *n == Some(*c)) {
+            Some(kept) => added[kept] = None,
+            None => gone.push((i, *c)),
+        }
+    }
+    for (i, c) in gone {
+        if played && uncolored(new.discard) == c {
+            moves.push((Some(new.discard), Spot::Hand(i), Spot::Discard));
+        }
+    }
+    for (i, c) in added.into_iter().enumerate() {
+        if let Some(c) = c {
+            moves.push((Some(c), Spot::Pot, Spot::Hand(i)));
+        }
+    }
+
+    let mut seats: Vec<_> = new.hand_sizes.iter().collect();
+    seats.sort();
+    for (&seat, &size) in seats {
+        let before = match old.hand_sizes.get(&seat) {
+            Some(&b) if Some(seat) != me => b,
+            _ => continue,
+        };
+        if size < before && played {
+            moves.push((Some(new.discard), Spot::Opponent(seat), Spot::Discard));
+        }
+        for _ in before..size {
+            moves.push((None, Spot::Pot, Spot::Opponent(seat)));
+        }
+    }
+    moves
+}
@@ -1,15 +1,27 @@
 use crate::event::OpaquePlayer;
 use crate::{
+    anim::{Animations, Spot, Table},
     card::{Card, Color},
+    card_view::{CardStyle, CardView, Chip, Palette, Swatch},
+    chat::{ChatMessage, Emote, MAX_MESSAGE_LEN},
     deck::Deck,
     event::{handle_events, Client, Error, Server},
+    hand::{self, SortMode},
+    hints,
+    history::Move,
+    i18n::{Language, Msg},
+    join_code,
+    keys::{self, Action, KeyBindings},
+    servers::ServerList,
 };
-use egui::{Align, Button, Layout, RichText, ScrollArea, TextEdit, Visuals};
-use std::time::{Duration, Instant};
+use egui::{Align, Button, Color32, Layout, RichText, ScrollArea, TextEdit, Visuals};
 use tokio::sync::mpsc::{channel, Receiver, Sender};
 
 use uuid::Uuid;
 
+/// Shown in the server field until the player enters their own.
+const DEFAULT_URL: &str = "http://server.com:1234";
+
 #[derive(serde::Deserialize, serde::Serialize)]
 #[serde(default)]
 pub struct OonoApp {
@@ -17,31 +29,51 @@ pub struct OonoApp {
     #[serde(skip)]
     my_hand: Deck,
     #[serde(skip)]
+    hand_version: u64,
+    /// The game version of the last update, sent back with every move.
+    #[serde(skip)]
+    game_version: u64,
+    #[serde(skip)]
     discard: Card,
     #[serde(skip)]
-    choosing_wild: Option<usize>,
+    choosing_wild: Option<Card>,
     #[serde(skip)]
     choosing_p4: bool,
     #[serde(skip)]
     tx: Sender<Client>,
     #[serde(skip)]
     rx: Receiver<Result<Server, Error>>,
+    /// Answers to the long poll, kept apart so only they end it.
+    #[serde(skip)]
+    poll_rx: Receiver<Result<Server, Error>>,
     #[serde(skip)]
     game_id_string: String,
     #[serde(skip)]
     game_id: Option<Uuid>,
     #[serde(skip)]
     gm_token: Option<Uuid>,
+    /// Short code for the game we created, shown in the GM panel.
+    #[serde(skip)]
+    join_code: Option<String>,
     #[serde(skip)]
     player_id: Option<Uuid>,
     #[serde(skip)]
     order_num: Option<usize>,
-    #[serde(skip)]
     player_name: String,
     #[serde(skip)]
-    error_msg: Option<String>,
+    error_msg: Option<Error>,
+    /// When updates were last asked for, in egui time. `Instant` doesn't work in the browser.
+    #[serde(skip)]
+    last_update: f64,
+    /// Whether a long poll for updates is waiting on the server.
     #[serde(skip)]
-    last_update: Instant,
+    polling: bool,
+    /// The `seen_ret` of the last update, sent with the next poll so only changes come back.
+    #[serde(skip)]
+    seen: u64,
+    /// The last update in full, for the next delta to be applied to.
+    #[serde(skip)]
+    latest: Option<Server>,
     #[serde(skip)]
     players: Vec<OpaquePlayer>,
     #[serde(skip)]
@@ -54,6 +86,41 @@ pub struct OonoApp {
     pot_size: usize,
     #[serde(skip)]
     winner: Option<OpaquePlayer>,
+    #[serde(skip)]
+    announcement: Option<String>,
+    #[serde(skip)]
+    announcement_closed: bool,
+    #[serde(skip)]
+    chat: Vec<ChatMessage>,
+    #[serde(skip)]
+    chat_draft: String,
+    #[serde(skip)]
+    moves: Vec<Move>,
+    /// Whether the GM allows hints in this game.
+    #[serde(skip)]
+    hints_allowed: bool,
+    /// Whether I want hints, when the GM allows them.
+    show_hints: bool,
+    #[serde(skip)]
+    animations: Animations,
+    card_style: CardStyle,
+    hand_sort: SortMode,
+    key_bindings: KeyBindings,
+    language: Language,
+    servers: ServerList,
+    #[serde(skip)]
+    show_settings: bool,
+    /// When the last polled update was asked for, to measure the server's latency.
+    #[serde(skip)]
+    update_sent: Option<f64>,
+    /// The card in my hand picked with the keyboard.
+    #[serde(skip)]
+    selected: Option<usize>,
+    #[serde(skip)]
+    show_keys: bool,
+    /// Waiting for the next key press to bind to this.
+    #[serde(skip)]
+    rebinding: Option<Action>,
     url: String,
 }
 
@@ -61,30 +128,56 @@ impl Default for OonoApp {
     fn default() -> Self {
         let (client_evt_tx, client_evt_rx) = channel(25);
         let (server_evt_tx, server_evt_rx) = channel(25);
-        handle_events(client_evt_rx, server_evt_tx, String::new());
+        let (poll_evt_tx, poll_evt_rx) = channel(1);
+        handle_events(client_evt_rx, server_evt_tx, poll_evt_tx, String::new());
         Self {
             // Example stuff:
             my_hand: Deck::new_empty(),
+            hand_version: 0,
+            game_version: 0,
             discard: Card::Wild(Color::None),
             choosing_wild: None,
             choosing_p4: false,
             tx: client_evt_tx,
             rx: server_evt_rx,
+            poll_rx: poll_evt_rx,
             game_id: None,
             gm_token: None,
+            join_code: None,
             player_id: None,
             order_num: None,
             player_name: String::new(),
             game_id_string: String::new(),
             error_msg: None,
-            last_update: Instant::now(),
+            last_update: 0.,
+            polling: false,
+            seen: 0,
+            latest: None,
             players: Vec::new(),
             reversed: false,
             whose_turn: 0,
             playing: false,
             pot_size: 0,
             winner: None,
-            url: "http://server.com:1234".to_string(),
+            announcement: None,
+            announcement_closed: false,
+            chat: Vec::new(),
+            chat_draft: String::new(),
+            moves: Vec::new(),
+            hints_allowed: true,
+            show_hints: true,
+            animations: Animations::default(),
+            card_style: CardStyle::default(),
+            hand_sort: SortMode::default(),
+            key_bindings: KeyBindings::default(),
+            language: Language::default(),
+            servers: ServerList::default(),
+            show_settings: false,
+            update_sent: None,
+            selected: None,
+            show_keys: false,
+            rebinding: None,
+            url: DEFAULT_URL.to_string(),
         }
     }
 }
@@ -95,10 +188,17 @@ impl OonoApp {
         // This is also where you can customized the look at feel of egui using
         // `cc.egui_ctx.set_visuals` and `cc.egui_ctx.set_fonts`.
         cc.egui_ctx.set_visuals(Visuals::dark());
-        if let Some(storage) = cc.storage {
-            return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+        let mut app: Self = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
+            .unwrap_or_default();
+        // in the browser, the page most likely came from the game server itself
+        if let Some(web) = &cc.integration_info.web_info {
+            if app.url == DEFAULT_URL {
+                app.url = web.location.origin.clone();
+            }
         }
-        Default::default()
+        app
     }
 }
 
@@ -112,44 +212,120 @@ impl eframe::App for OonoApp {
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         let Self {
             my_hand,
+            hand_version,
+            game_version,
             discard,
             choosing_wild,
             choosing_p4,
             tx,
             rx,
+            poll_rx,
             game_id,
             gm_token,
+            join_code,
             player_id,
             order_num,
             player_name,
             game_id_string,
             error_msg,
             last_update,
+            polling,
+            seen,
+            latest,
             players,
             reversed,
             whose_turn,
             playing,
             pot_size,
             winner,
+            announcement,
+            announcement_closed,
+            chat,
+            chat_draft,
+            moves,
+            hints_allowed,
+            show_hints,
+            animations,
+            card_style,
+            hand_sort,
+            key_bindings,
+            language,
+            servers,
+            show_settings,
+            update_sent,
+            selected,
+            show_keys,
+            rebinding,
             url,
         } = self;
-        let now = Instant::now();
-        if now.duration_since(*last_update) > Duration::from_millis(500) {
+        let lang = *language;
+        let t = |msg| lang.text(msg);
+        let now = ctx.input().time;
+        if !*polling && now - *last_update > 0.5 {
             if let (Some(game_id), Some(player_id)) = (*game_id, *player_id) {
-                tx.try_send(Client::RequestUpdate { game_id, player_id });
-                *last_update = now;
+                let since = *seen;
+                if tx
+                    .try_send(Client::RequestUpdate {
+                        game_id,
+                        player_id,
+                        since,
+                    })
+                    .is_ok()
+                {
+                    *polling = true;
+                    *last_update = now;
+                    *update_sent = Some(now);
+                }
             }
         }
-        if let Ok(evt) = rx.try_recv() {
+        let polled = poll_rx.try_recv().ok();
+        if let Some(polled) = &polled {
+            *polling = false;
+            if polled.is_err() {
+                *update_sent = None;
+            }
+        }
+        if let Some(evt) = polled.or_else(|| rx.try_recv().ok()) {
+            let evt = match evt {
+                Ok(Server::UpdateChanged {
+                    seen_ret,
+                    held_ms_ret,
+                    delta,
+                }) => {
+                    if let Some(sent) = update_sent.take() {
+                        let ms = ((now - sent) * 1000.) as u64;
+                        servers.record_latency(url, ms.saturating_sub(held_ms_ret) as u32);
+                    }
+                    // a delta is made against the update just before it, which we may not have
+                    let base = match seen_ret == *seen + 1 {
+                        true => latest.as_ref(),
+                        false => None,
+                    };
+                    match delta.apply(base) {
+                        Some(update) => {
+                            *seen = seen_ret;
+                            *latest = Some(update.clone());
+                            Ok(update)
+                        }
+                        None => {
+                            *seen = 0;
+                            Ok(Server::NoChange)
+                        }
+                    }
+                }
+                evt => evt,
+            };
             match evt {
                 Ok(evt) => match evt {
                     Server::GameCreated {
                         game_id_ret,
                         gm_token_ret,
+                        join_code_ret,
                     } => {
                         *gm_token = Some(gm_token_ret);
+                        *join_code = Some(join_code_ret);
                         *game_id = Some(game_id_ret);
-                        tx.try_send(Client::JoinGame {
+                        let _ = tx.try_send(Client::JoinGame {
                             game_id: game_id_ret,
                             name: player_name.to_string(),
                         });
@@ -163,137 +339,282 @@ impl eframe::App for OonoApp {
                         *order_num = Some(order_num_ret);
                         *game_id = Some(game_id_ret);
                     }
-                    Server::GameStarted => {
-                        if let (Some(game_id), Some(player_id)) = (*game_id, *player_id) {
-                            tx.try_send(Client::RequestUpdate { game_id, player_id });
-                            *last_update = now;
-                        }
-                    }
+                    // the long poll brings the new state, deltas were made whole above
+                    Server::GameStarted
+                    | Server::HintsSet
+                    | Server::CardPlaced
+                    | Server::CardDrawn
+                    | Server::ChatSent
+                    | Server::EmoteSent
+                    | Server::UpdateChanged { .. }
+                    | Server::NoChange => {}
                     Server::UpdateResponse {
                         hand_ret,
+                        hand_version_ret,
+                        version_ret,
                         discard_ret,
                         reversed_ret,
                         players_ret,
                         whose_turn_ret,
                         playing_ret,
                         pot_size_ret,
+                        announcement_ret,
+                        chat_ret,
+                        moves_ret,
+                        hints_ret,
                     } => {
+                        let hand_ret = Deck(hand::arrange(
+                            *hand_sort,
+                            hand_ret.0,
+                            &my_hand.0,
+                            &discard_ret,
+                        ));
+                        if playing_ret {
+                            let table = Table {
+                                hand: hand_ret.0.clone(),
+                                discard: discard_ret,
+                                hand_sizes: players_ret
+                                    .iter()
+                                    .map(|p| (p.order_num, p.hand_size))
+                                    .collect(),
+                            };
+                            animations.update(table, *order_num, ctx.input().time);
+                        }
+                        *chat = chat_ret;
+                        *moves = moves_ret;
+                        *hints_allowed = hints_ret;
+                        if announcement_ret != *announcement {
+                            *announcement_closed = false;
+                            *announcement = announcement_ret;
+                        }
                         *players = players_ret;
                         *my_hand = hand_ret;
+                        *hand_version = hand_version_ret;
+                        *game_version = version_ret;
                         *discard = discard_ret;
                         *reversed = reversed_ret;
                         *whose_turn = whose_turn_ret;
                         *playing = playing_ret;
                         *pot_size = pot_size_ret;
                     }
-                    Server::CardPlaced => {
-                        if let (Some(game_id), Some(player_id)) = (*game_id, *player_id) {
-                            tx.try_send(Client::RequestUpdate { game_id, player_id });
-                            *last_update = now;
-                        }
-                    }
-                    Server::CardDrawn => {
-                        if let (Some(game_id), Some(player_id)) = (*game_id, *player_id) {
-                            tx.try_send(Client::RequestUpdate { game_id, player_id });
-                            *last_update = now;
-                        }
-                    }
                     Server::PlayerWon { order_num } => {
                         if let Some(w) = players.iter().find(|p| p.order_num == order_num) {
                             let w = (*w).clone();
                             *winner = Some(w);
                         } else {
-                            *error_msg = Some("A nonexistant player has won the game. Please contact the administrator.".to_owned());
+                            *error_msg = Some(Error::UnknownWinner { order_num });
                         }
                     }
                 },
-                Err(e) => *error_msg = Some(e.to_string()),
+                Err(e) => {
+                    *error_msg = Some(e);
+                }
+            }
+        }
+
+        let my_turn = *playing && *order_num == Some(*whose_turn);
+        let hint = match (my_turn && *hints_allowed && *show_hints, *order_num) {
+            (true, Some(me)) if !players.is_empty() => {
+                let next = match *reversed {
+                    true => (me + players.len() - 1) % players.len(),
+                    false => (me + 1) % players.len(),
+                };
+                let next_hand_size = players
+                    .iter()
+                    .find(|p| p.order_num == next)
+                    .map(|p| p.hand_size);
+                hints::best(&my_hand.0, discard, next_hand_size)
+            }
+            _ => None,
+        };
+        let last_card = my_hand.0.len().checked_sub(1);
+        if selected.is_some() && *selected > last_card {
+            *selected = last_card;
+        }
+        let mut play = None;
+        let mut pick = None;
+        if let Some(action) = *rebinding {
+            if let Some(key) = keys::first_pressed(&ctx.input()) {
+                key_bindings.rebind(action, key);
+                *rebinding = None;
+            }
+        } else if !ctx.wants_keyboard_input() {
+            let input = ctx.input();
+            let pressed = |action| key_bindings.pressed(&input, action);
+            if pressed(Action::Help) {
+                *show_keys = !*show_keys;
+            }
+            if choosing_wild.is_some() {
+                if pressed(Action::Cancel) {
+                    *choosing_wild = None;
+                    *choosing_p4 = false;
+                }
+                pick = [
+                    (Action::Red, Color::Red),
+                    (Action::Green, Color::Green),
+                    (Action::Yellow, Color::Yellow),
+                    (Action::Blue, Color::Blue),
+                ]
+                .into_iter()
+                .find(|(action, _)| pressed(*action))
+                .map(|(_, color)| color);
+            } else if my_turn {
+                if pressed(Action::SelectLeft) {
+                    *selected = match *selected {
+                        Some(i) => Some(i.saturating_sub(1)),
+                        None => last_card,
+                    };
+                }
+                if pressed(Action::SelectRight) {
+                    *selected = match *selected {
+                        Some(i) => last_card.map(|last| (i + 1).min(last)),
+                        None => last_card.map(|_| 0),
+                    };
+                }
+                if pressed(Action::Play) {
+                    play = *selected;
+                }
+                if pressed(Action::Draw) {
+                    if let (Some(game_id), Some(player_id)) = (*game_id, *player_id) {
+                        let _ = tx.try_send(Client::DrawCard {
+                            game_id,
+                            player_id,
+                            version: *game_version,
+                        });
+                    }
+                }
             }
         }
 
         if game_id.is_none() {
-            egui::Window::new("Welcome to Oono :)")
+            egui::Window::new(t(Msg::Welcome))
                 //.min_width(300.)
                 .resizable(false)
                 .show(ctx, |ui| {
                     ui.horizontal(|ui| {
                         ui.vertical(|ui| {
-                            ui.heading("Create game");
+                            ui.heading(t(Msg::CreateGame));
                             if ui
-                                .add_enabled(!player_name.is_empty(), Button::new("Create"))
+                                .add_enabled(!player_name.is_empty(), Button::new(t(Msg::Create)))
                                 .clicked()
                             {
-                            tx.try_send(Client::UpdateServer { url: url.to_string() });
-
-                                tx.try_send(Client::CreateGame );
+                                let _ = tx.try_send(Client::UpdateServer {
+                                    url: url.to_string(),
+                                });
+                                servers.used(url);
 
+                                let _ = tx.try_send(Client::CreateGame);
                             }
                         });
                         ui.separator();
                         ui.vertical(|ui| {
-                            ui.heading("Join game");
+                            ui.heading(t(Msg::JoinGame));
                             ui.horizontal(|ui| {
-                                ui.label("Game ID: ");
+                                ui.label(format!("{}: ", t(Msg::GameIdOrCode)));
                                 ui.text_edit_singleline(game_id_string);
                             });
                             if ui
                                 .add_enabled(
                                     !player_name.is_empty() && !game_id_string.is_empty(),
-                                    Button::new("Join"),
+                                    Button::new(t(Msg::Join)),
                                 )
                                 .clicked()
                             {
-                                match Uuid::parse_str(game_id_string) {
-                                    Ok(id) => {
-                                         tx.try_send(Client::UpdateServer { url: url.to_string() });
-                                        
-                                        tx.try_send(Client::JoinGame {
-                                            game_id: id,
-                                            name: player_name.to_string(),
+                                let name = player_name.to_string();
+                                let join = match Uuid::parse_str(game_id_string.trim()) {
+                                    Ok(game_id) => Some(Client::JoinGame { game_id, name }),
+                                    Err(_) => join_code::normalize(game_id_string)
+                                        .map(|code| Client::JoinByCode { code, name }),
+                                };
+                                match join {
+                                    Some(join) => {
+                                        let _ = tx.try_send(Client::UpdateServer {
+                                            url: url.to_string(),
                                         });
+                                        servers.used(url);
+
+                                        let _ = tx.try_send(join);
 
                                         *error_msg = None;
                                     }
-                                    Err(e) => {
-                                        *error_msg =
-                                            Some(format!("Error:  {}  is not a valid UUID.\nThe error generated was:\n{}", game_id_string, e));
+                                    None => {
+                                        *error_msg = Some(Error::InvalidJoinCode {
+                                            code: game_id_string.to_string(),
+                                        });
                                     }
                                 }
                             }
-
                         });
                     });
                     ui.separator();
-                    
+
                     ui.horizontal(|ui| {
-                        ui.label("Name: ");
+                        ui.label(format!("{}: ", t(Msg::Name)));
                         ui.text_edit_singleline(player_name);
                     });
-                    
+
                     ui.horizontal(|ui| {
-                        ui.label("Server: ");
-                        if ui.text_edit_singleline(url).changed() {
-                            tx.try_send(Client::UpdateServer { url: url.to_string() });
+                        ui.label(format!("{}: ", t(Msg::Server)));
+                        let mut changed = ui.text_edit_singleline(url).changed();
+                        if !servers.0.is_empty() {
+                            egui::ComboBox::from_id_source("recent_servers")
+                                .selected_text(t(Msg::RecentServers))
+                                .show_ui(ui, |ui| {
+                                    for s in &servers.0 {
+                                        let label = match s.latency_ms {
+                                            Some(ms) => format!("{}  ({} ms)", s.label(), ms),
+                                            None => s.label().to_string(),
+                                        };
+                                        if ui.selectable_label(*url == s.url, label).clicked() {
+                                            *url = s.url.clone();
+                                            changed = true;
+                                        }
+                                    }
+                                });
+                        }
+                        if changed {
+                            let _ = tx.try_send(Client::UpdateServer {
+                                url: url.to_string(),
+                            });
                         };
-
                     });
-
                 });
         }
 
         if let Some(id) = *game_id {
             if gm_token.is_some() {
-                egui::Window::new("GM panel")
+                egui::Window::new(t(Msg::GmPanel))
                     //.min_width(300.)
                     .resizable(false)
                     .show(ctx, |ui| {
-                        ui.label("Game ID");
+                        if let Some(code) = join_code {
+                            ui.label(t(Msg::JoinCode));
+                            ui.heading(RichText::new(code.as_str()).monospace().strong());
+                        }
+                        ui.label(t(Msg::GameId));
                         ui.add(TextEdit::singleline(&mut id.to_string()).code_editor());
-                        if !*playing && ui.button("Start game").clicked() {
-                            tx.try_send(Client::UpdateServer { url: url.to_string() });
+                        if !*playing && ui.button(t(Msg::StartGame)).clicked() {
+                            let _ = tx.try_send(Client::UpdateServer {
+                                url: url.to_string(),
+                            });
                             if let (Some(game_id), Some(gm_token)) = (*game_id, *gm_token) {
                                 ui.separator();
-                                tx.try_send(Client::StartGame { game_id, gm_token });
+                                let _ = tx.try_send(Client::StartGame {
+                                    game_id,
+                                    gm_token,
+                                    version: *game_version,
+                                });
+                            }
+                        }
+                        let mut allowed = *hints_allowed;
+                        if ui.checkbox(&mut allowed, t(Msg::AllowHints)).changed() {
+                            if let (Some(game_id), Some(gm_token)) = (*game_id, *gm_token) {
+                                *hints_allowed = allowed;
+                                let _ = tx.try_send(Client::SetHints {
+                                    game_id,
+                                    gm_token,
+                                    allowed,
+                                });
                             }
                         }
                     });
@@ -301,33 +622,43 @@ impl eframe::App for OonoApp {
         }
 
         if let Some(w) = winner.clone() {
-            egui::Window::new("Winner")
+            egui::Window::new(t(Msg::Winner))
                 //.min_width(300.)
                 .resizable(false)
                 .show(ctx, |ui| {
-                    ui.heading(format!("{} has won the game!", w.name));
+                    ui.heading(lang.tr(Msg::HasWon, &[&w.name]));
                     ui.horizontal(|ui| {
-                        if ui.button("New game").clicked() {
+                        if ui.button(t(Msg::NewGame)).clicked() {
                             *my_hand = Deck::new_empty();
+                            *hand_version = 0;
+                            *game_version = 0;
                             *discard = Card::Wild(Color::None);
                             *choosing_wild = None;
                             *choosing_p4 = false;
                             *game_id = None;
                             *gm_token = None;
+                            *join_code = None;
+                            *hints_allowed = true;
                             *player_id = None;
                             *order_num = None;
-                            *player_name = String::new();
                             *game_id_string = String::new();
                             *error_msg = None;
-                            *last_update = Instant::now();
+                            *last_update = now;
+                            *seen = 0;
+                            *latest = None;
                             *players = Vec::new();
                             *reversed = false;
                             *whose_turn = 0;
                             *playing = false;
                             *pot_size = 0;
                             *winner = None;
+                            animations.reset();
+                            *selected = None;
+                            *chat = Vec::new();
+                            *chat_draft = String::new();
+                            *moves = Vec::new();
                         }
-                        if ui.button("quit").clicked() {
+                        if ui.button(t(Msg::Quit)).clicked() {
                             frame.quit();
                         }
                     });
@@ -337,52 +668,135 @@ impl eframe::App for OonoApp {
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             // The top panel is often a good place for a menu bar:
             egui::menu::bar(ui, |ui| {
-                ui.menu_button("File", |ui| {
-                    if ui.button("Quit").clicked() {
+                ui.menu_button(t(Msg::File), |ui| {
+                    if ui.button(t(Msg::Settings)).clicked() {
+                        *show_settings = true;
+                        ui.close_menu();
+                    }
+                    if ui.button(t(Msg::Quit)).clicked() {
                         frame.quit();
                     }
                 });
+                ui.menu_button(t(Msg::Accessibility), |ui| {
+                    ui.checkbox(&mut card_style.shapes, t(Msg::ColorShapes));
+                    ui.separator();
+                    ui.label(t(Msg::CardColors));
+                    for palette in Palette::ALL {
+                        ui.radio_value(&mut card_style.palette, palette, t(palette.name()));
+                    }
+                });
+                ui.menu_button(t(Msg::Language), |ui| {
+                    for l in Language::ALL {
+                        ui.radio_value(language, l, l.name());
+                    }
+                });
+                ui.menu_button(t(Msg::Help), |ui| {
+                    if ui.button(t(Msg::KeyboardControls)).clicked() {
+                        *show_keys = true;
+                        ui.close_menu();
+                    }
+                    ui.checkbox(show_hints, t(Msg::ShowHints));
+                });
             });
         });
 
         egui::SidePanel::left("side_panel").show(ctx, |ui| {
-            ui.heading(format!("You are {}", *player_name));
-            ui.heading("Opponents");
+            ui.heading(lang.tr(Msg::YouAre, &[player_name]));
+            ui.heading(t(Msg::Opponents));
             ui.separator();
             ScrollArea::vertical().show(ui, |ui| {
-                ui.label(format!("whos turn: {}", *whose_turn));
+                ui.label(lang.tr(Msg::WhoseTurn, &[whose_turn]));
 
                 for player in &*players {
-                    egui::containers::Frame::group(ctx.style().as_ref()).show(ui, |ui| {
-                        ui.horizontal(|ui| {
-                            ui.label(&player.name);
-                            //ui.label(format!("order: {}", player.order_num));
-
-                            if *playing {
-                                if player.hand_size == 1 {
-                                    ui.label("UNO CARDS!");
-                                } else {
-                                    ui.label(format!("{} cards", player.hand_size));
-                                }
-                                if *whose_turn == player.order_num {
-                                    ui.label(RichText::new("Playing now").strong());
+                    let frame =
+                        egui::containers::Frame::group(ctx.style().as_ref()).show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(&player.name);
+                                //ui.label(format!("order: {}", player.order_num));
+                                if let Some(emote) = player.emote {
+                                    ui.label(
+                                        RichText::new(t(Msg::emote(emote)))
+                                            .strong()
+                                            .color(egui::color::Color32::from_rgb(255, 200, 0)),
+                                    );
                                 }
-                                if (!*reversed
-                                    && *whose_turn
-                                        == (player.order_num + players.len() - 1) % players.len())
-                                    || (*reversed
+
+                                if *playing {
+                                    if player.hand_size == 1 {
+                                        ui.label(t(Msg::LastCard));
+                                    } else {
+                                        ui.label(lang.tr(Msg::CardCount, &[&player.hand_size]));
+                                    }
+                                    if *whose_turn == player.order_num {
+                                        ui.label(RichText::new(t(Msg::PlayingNow)).strong());
+                                    }
+                                    if (!*reversed
                                         && *whose_turn
-                                            == (player.order_num + players.len() + 1)
+                                            == (player.order_num + players.len() - 1)
                                                 % players.len())
-                                {
-                                    ui.label(RichText::new("Playing next").strong());
+                                        || (*reversed
+                                            && *whose_turn
+                                                == (player.order_num + players.len() + 1)
+                                                    % players.len())
+                                    {
+                                        ui.label(RichText::new(t(Msg::PlayingNext)).strong());
+                                    }
                                 }
-                            }
+                            });
                         });
-                    });
+                    animations.place(Spot::Opponent(player.order_num), frame.response.rect);
                 }
             });
 
+            if let (Some(game_id), Some(player_id)) = (*game_id, *player_id) {
+                ui.separator();
+                ui.horizontal_wrapped(|ui| {
+                    for emote in Emote::ALL {
+                        if ui.button(t(Msg::emote(emote))).clicked() {
+                            let _ = tx.try_send(Client::SendEmote {
+                                game_id,
+                                player_id,
+                                emote,
+                            });
+                        }
+                    }
+                });
+                ui.separator();
+                ui.heading(t(Msg::Chat));
+                ScrollArea::vertical()
+                    .id_source("chat")
+                    .max_height(200.)
+                    .stick_to_bottom()
+                    .show(ui, |ui| {
+                        for msg in &*chat {
+                            ui.horizontal_wrapped(|ui| {
+                                ui.label(RichText::new(format!("{}:", msg.name)).strong());
+                                ui.label(&msg.text);
+                            });
+                        }
+                    });
+                ui.horizontal(|ui| {
+                    let input =
+                        ui.add(TextEdit::singleline(chat_draft).hint_text(t(Msg::SaySomething)));
+                    let enter = input.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+                    let sendable = !chat_draft.trim().is_empty()
+                        && chat_draft.chars().count() <= MAX_MESSAGE_LEN;
+                    if (ui
+                        .add_enabled(sendable, Button::new(t(Msg::Send)))
+                        .clicked()
+                        || enter)
+                        && sendable
+                    {
+                        let _ = tx.try_send(Client::SendChat {
+                            game_id,
+                            player_id,
+                            message: std::mem::take(chat_draft),
+                        });
+                        input.request_focus();
+                    }
+                });
+            }
+
             ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
                 ui.horizontal(|ui| {
                     ui.spacing_mut().item_spacing.x = 0.0;
@@ -391,224 +805,305 @@ impl eframe::App for OonoApp {
             });
         });
 
-        if let Some(msg) = error_msg.clone() {
-            egui::Window::new("Error")
+        if let Some(msg) = announcement.clone() {
+            if !*announcement_closed {
+                egui::Window::new(t(Msg::ServerMessage))
+                    .auto_sized()
+                    .show(ctx, |ui| {
+                        ui.label(RichText::new(msg).strong());
+                        if ui.button(format!("    {}    ", t(Msg::Close))).clicked() {
+                            *announcement_closed = true;
+                        }
+                    });
+            }
+        }
+
+        if let Some(msg) = error_msg.as_ref().map(|e| e.localize(lang)) {
+            egui::Window::new(t(Msg::Error))
                 //.min_width(300.)
                 .auto_sized()
                 .show(ctx, |ui| {
                     ui.add(TextEdit::multiline(&mut msg.to_string()).code_editor());
-                    if ui.button("    close    ").clicked() {
+                    if ui.button(format!("    {}    ", t(Msg::Close))).clicked() {
                         *error_msg = None;
                     }
                 });
         }
 
-        if let Some(index) = *choosing_wild {
-            egui::Window::new("Select a color").show(ctx, |ui| {
+        if let Some(card) = *choosing_wild {
+            egui::Window::new(t(Msg::SelectColor)).show(ctx, |ui| {
                 ui.horizontal(|ui| {
-                    //red
-                    if ui
-                        .add(
-                            Button::new(
-                                RichText::new("RED")
-                                    .color(egui::color::Color32::from_rgb(50, 0, 0)),
-                            )
-                            .fill(egui::color::Color32::from_rgb(255, 0, 0)),
-                        )
-                        .clicked()
-                    {
-                        if let Some(game_id) = *game_id {
-                            if let Some(player_id) = *player_id {
-                                tx.try_send(Client::PlaceCard {
-                                    game_id,
-                                    player_id,
-                                    index,
-                                    color: Some(Color::Red),
-                                });
-                                *choosing_p4 = false;
-                                *choosing_wild = None;
-                            }
+                    for color in [Color::Red, Color::Green, Color::Yellow, Color::Blue] {
+                        if ui.add(Swatch::new(color, *card_style, lang)).clicked() {
+                            pick = Some(color);
                         }
                     }
+                });
+                let key = |action| keys::key_name(key_bindings.key(action));
+                ui.label(lang.tr(
+                    Msg::ColorKeys,
+                    &[
+                        &key(Action::Red),
+                        &key(Action::Green),
+                        &key(Action::Yellow),
+                        &key(Action::Blue),
+                        &key(Action::Cancel),
+                    ],
+                ));
+            });
+            if let (Some(color), Some(game_id), Some(player_id)) = (pick, *game_id, *player_id) {
+                let _ = tx.try_send(Client::PlaceCard {
+                    game_id,
+                    player_id,
+                    card,
+                    color: Some(color),
+                    hand_version: *hand_version,
+                    version: *game_version,
+                });
+                *choosing_p4 = false;
+                *choosing_wild = None;
+            }
+        }
 
-                    //green
-                    if ui
-                        .add(
-                            Button::new(
-                                RichText::new("GREEN")
-                                    .color(egui::color::Color32::from_rgb(0, 50, 0)),
-                            )
-                            .fill(egui::color::Color32::from_rgb(0, 255, 0)),
-                        )
-                        .clicked()
-                    {
-                        if let Some(game_id) = *game_id {
-                            if let Some(player_id) = *player_id {
-                                tx.try_send(Client::PlaceCard {
-                                    game_id,
-                                    player_id,
-                                    index,
-                                    color: Some(Color::Green),
-                                });
-                                *choosing_p4 = false;
-                                *choosing_wild = None;
+        if *show_settings {
+            egui::Window::new(t(Msg::Settings))
+                .open(show_settings)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{}: ", t(Msg::Name)));
+                        // the server already knows us by the old name
+                        ui.add_enabled(game_id.is_none(), TextEdit::singleline(player_name));
+                    });
+                    ui.separator();
+                    ui.heading(t(Msg::SavedServers));
+                    let mut forget = None;
+                    egui::Grid::new("servers").striped(true).show(ui, |ui| {
+                        ui.strong(t(Msg::Nickname));
+                        ui.strong(t(Msg::Server));
+                        ui.strong(t(Msg::Latency));
+                        ui.end_row();
+                        for (i, s) in servers.0.iter_mut().enumerate() {
+                            ui.add(TextEdit::singleline(&mut s.nickname).desired_width(100.));
+                            ui.label(&s.url);
+                            ui.label(
+                                s.latency_ms
+                                    .map_or(String::new(), |ms| format!("{} ms", ms)),
+                            );
+                            if ui.button(t(Msg::Forget)).clicked() {
+                                forget = Some(i);
                             }
+                            ui.end_row();
                         }
+                    });
+                    if let Some(i) = forget {
+                        servers.0.remove(i);
                     }
+                });
+        }
 
-                    //yellow
-                    if ui
-                        .add(
-                            Button::new(
-                                RichText::new("YELLOW")
-                                    .color(egui::color::Color32::from_rgb(0, 50, 50)),
-                            )
-                            .fill(egui::color::Color32::from_rgb(255, 255, 0)),
-                        )
-                        .clicked()
-                    {
-                        if let Some(game_id) = *game_id {
-                            if let Some(player_id) = *player_id {
-                                tx.try_send(Client::PlaceCard {
-                                    game_id,
-                                    player_id,
-                                    index,
-                                    color: Some(Color::Yellow),
-                                });
-                                *choosing_p4 = false;
-                                *choosing_wild = None;
+        if *show_keys {
+            egui::Window::new(t(Msg::KeyboardControls))
+                .open(show_keys)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    egui::Grid::new("keys").striped(true).show(ui, |ui| {
+                        for action in Action::ALL {
+                            ui.label(t(action.describe()));
+                            let text = match *rebinding == Some(action) {
+                                true => t(Msg::PressAKey).to_string(),
+                                false => keys::key_name(key_bindings.key(action)),
+                            };
+                            if ui
+                                .button(text)
+                                .on_hover_text(t(Msg::ClickToRebind))
+                                .clicked()
+                            {
+                                *rebinding = Some(action);
                             }
+                            ui.end_row();
                         }
+                    });
+                    ui.separator();
+                    if ui.button(t(Msg::ResetDefaults)).clicked() {
+                        key_bindings.reset();
+                        *rebinding = None;
                     }
+                });
+        }
 
-                    //blue
-                    if ui
-                        .add(
-                            Button::new(
-                                RichText::new("BLUE")
-                                    .color(egui::color::Color32::from_rgb(0, 0, 0)),
-                            )
-                            .fill(egui::color::Color32::from_rgb(0, 125, 255)),
-                        )
-                        .clicked()
-                    {
-                        if let Some(game_id) = *game_id {
-                            if let Some(player_id) = *player_id {
-                                tx.try_send(Client::PlaceCard {
-                                    game_id,
-                                    player_id,
-                                    index,
-                                    color: Some(Color::Blue),
-                                });
-                                *choosing_p4 = false;
-                                *choosing_wild = None;
-                            }
+        if *playing {
+            egui::SidePanel::right("history_panel").show(ctx, |ui| {
+                ui.heading(t(Msg::History));
+                ui.separator();
+                ScrollArea::vertical()
+                    .id_source("history")
+                    .stick_to_bottom()
+                    .show(ui, |ui| {
+                        for m in &*moves {
+                            ui.horizontal(|ui| match m {
+                                Move::Played { name, card } => {
+                                    ui.label(lang.tr(Msg::MovePlayed, &[name]));
+                                    ui.add(Chip::new(*card, *card_style))
+                                        .on_hover_text(t(Msg::color(card.color())));
+                                }
+                                Move::Drew { name } => {
+                                    ui.label(lang.tr(Msg::MoveDrew, &[name]));
+                                }
+                                Move::DrewPenalty { name, count } => {
+                                    ui.label(lang.tr(Msg::MoveDrewPenalty, &[name, count]));
+                                }
+                                Move::Skipped { name } => {
+                                    ui.label(lang.tr(Msg::MoveSkipped, &[name]));
+                                }
+                                Move::Reversed => {
+                                    ui.label(t(Msg::MoveReversed));
+                                }
+                            });
                         }
-                    }
-                });
+                    });
             });
         }
 
+        let mut drag = None;
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.with_layout(Layout::top_down(Align::Center), |ui| {
-                ui.add_enabled_ui(
-                    *playing
-                        && if let Some(n) = *order_num {
-                            n == *whose_turn
-                        } else {
-                            false
-                        },
-                    |ui| {
-                        ui.label(RichText::new("My Hand").text_style(egui::TextStyle::Heading));
-                        ScrollArea::horizontal().max_height(25.).show(ui, |ui| {
-                            ui.with_layout(Layout::left_to_right(), |ui| {
-                                for (idx, c) in my_hand.0.iter_mut().enumerate() {
-                                    let (dim, bright) = match c.color() {
-                                        Color::Red => (
-                                            egui::color::Color32::from_rgb(50, 0, 0),
-                                            egui::color::Color32::from_rgb(255, 0, 0),
-                                        ),
-                                        Color::Green => (
-                                            egui::color::Color32::from_rgb(0, 50, 0),
-                                            egui::color::Color32::from_rgb(0, 255, 0),
-                                        ),
-                                        Color::Yellow => (
-                                            egui::color::Color32::from_rgb(0, 50, 50),
-                                            egui::color::Color32::from_rgb(255, 255, 0),
-                                        ),
-                                        Color::Blue => (
-                                            egui::color::Color32::from_rgb(0, 0, 0),
-                                            egui::color::Color32::from_rgb(0, 125, 255),
-                                        ),
-                                        Color::None => (
-                                            egui::color::Color32::from_rgb(227, 227, 227),
-                                            egui::color::Color32::from_rgb(70, 70, 70),
-                                        ),
-                                    };
-                                    let text =
-                                        RichText::new(format!("{}\n{}", c, c.color())).color(dim);
-                                    ui.vertical(|ui| {
-                                        if !c.is_valid_on(discard) {
-                                            ui.add_enabled(
-                                                false,
-                                                Button::new(text).wrap(false).fill(bright),
-                                            );
-                                        } else if ui
-                                            .add(Button::new(text).wrap(false).fill(bright))
-                                            .clicked()
-                                        {
-                                            if *c == Card::PlusFour(Color::None) {
-                                                *choosing_wild = Some(idx);
-                                                *choosing_p4 = true;
-                                            } else if *c == Card::Wild(Color::None) {
-                                                *choosing_wild = Some(idx);
-                                                *choosing_p4 = false;
-                                            } else if let (Some(game_id), Some(player_id)) =
-                                                (*game_id, *player_id)
-                                            {
-                                                tx.try_send(Client::PlaceCard {
-                                                    game_id,
-                                                    player_id,
-                                                    index: idx,
-                                                    color: None,
-                                                });
-                                            }
-                                        }
-                                    });
-                                }
-                            });
-                        });
-
-                        if ui.button("Draw card").clicked() {
-                            if let (Some(game_id), Some(player_id)) = (*game_id, *player_id) {
-                                tx.try_send(Client::DrawCard { game_id, player_id });
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(t(Msg::MyHand)).text_style(egui::TextStyle::Heading));
+                    let before = *hand_sort;
+                    egui::ComboBox::from_id_source("hand_sort")
+                        .selected_text(format!("{}: {}", t(Msg::SortBy), t(hand_sort.name())))
+                        .show_ui(ui, |ui| {
+                            for mode in SortMode::ALL {
+                                ui.selectable_value(hand_sort, mode, t(mode.name()));
+                            }
+                        })
+                        .response
+                        .on_hover_text(t(Msg::DragToArrange));
+                    if *hand_sort != before {
+                        my_hand.0 =
+                            hand::arrange(*hand_sort, my_hand.0.clone(), &my_hand.0, discard);
+                        *selected = None;
+                    }
+                });
+                ScrollArea::horizontal().show(ui, |ui| {
+                    ui.with_layout(Layout::left_to_right(), |ui| {
+                        let mut rects = vec![];
+                        let mut held = None;
+                        for (idx, c) in my_hand.0.iter().enumerate() {
+                            let card = CardView::new(*c)
+                                .playable(c.is_valid_on(discard))
+                                .interactive(my_turn)
+                                .draggable(true)
+                                .selected(my_turn && *selected == Some(idx))
+                                .hinted(hint.is_some_and(|h| h.index == idx))
+                                .style(*card_style);
+                            let card = ui.add(card);
+                            animations.place(Spot::Hand(idx), card.rect);
+                            rects.push(card.rect);
+                            if card.clicked() {
+                                play = Some(idx);
+                            }
+                            if card.dragged() || card.drag_released() {
+                                held = Some((idx, card.drag_released()));
                             }
                         }
-                        ui.label(format!("{} cards remain in the pot.", *pot_size));
+                        // the dragged card goes where the pointer is, between the others
+                        let pointer = ui.input().pointer.hover_pos();
+                        if let (Some((from, released)), Some(pos)) = (held, pointer) {
+                            let to = rects
+                                .iter()
+                                .position(|r| pos.x < r.right())
+                                .unwrap_or(rects.len() - 1);
+                            let x = match to > from {
+                                true => rects[to].right(),
+                                false => rects[to].left(),
+                            };
+                            ui.painter().vline(
+                                x,
+                                rects[to].y_range(),
+                                ui.visuals().selection.stroke,
+                            );
+                            if released {
+                                drag = Some((from, to));
+                            }
+                        }
+                    });
+                });
 
+                if let Some(hint) = hint {
+                    ui.label(RichText::new(hint.reason.localize(lang)).color(Color32::GOLD));
+                }
+                ui.add_enabled_ui(my_turn, |ui| {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.vertical(|ui| {
+                            ui.label(t(Msg::Pot));
+                            let pot = ui
+                                .add(CardView::pot(*pot_size).height(120.).style(*card_style))
+                                .on_hover_text(t(Msg::DrawACard));
+                            animations.place(Spot::Pot, pot.rect);
+                            if pot.clicked() || ui.button(t(Msg::DrawCard)).clicked() {
+                                if let (Some(game_id), Some(player_id)) = (*game_id, *player_id) {
+                                    let _ = tx.try_send(Client::DrawCard {
+                                        game_id,
+                                        player_id,
+                                        version: *game_version,
+                                    });
+                                }
+                            }
+                            ui.label(lang.tr(Msg::PotRemaining, &[pot_size]));
+                        });
                         ui.separator();
-                        ui.label("DISCARD");
-                        ui.separator();
-
-                        ui.add(
-                            Button::new(
-                                RichText::new(format!("{}\n{}", discard, discard.color()))
-                                    .color(egui::color::Color32::from_rgb(0, 0, 0))
-                                    .size(25.),
-                            )
-                            .fill(match discard.color() {
-                                Color::Red => egui::color::Color32::from_rgb(255, 0, 0),
-                                Color::Green => egui::color::Color32::from_rgb(0, 255, 0),
-                                Color::Yellow => egui::color::Color32::from_rgb(255, 255, 0),
-                                Color::Blue => egui::color::Color32::from_rgb(0, 125, 255),
-                                Color::None => egui::color::Color32::from_rgb(70, 70, 70),
-                            }),
-                        );
-                    },
-                );
+                        ui.vertical(|ui| {
+                            ui.label(t(Msg::Discard));
+                            let shown = ui.add(
+                                CardView::new(*discard)
+                                    .height(120.)
+                                    .interactive(false)
+                                    .style(*card_style),
+                            );
+                            animations.place(Spot::Discard, shown.rect);
+                            ui.label(
+                                RichText::new(t(Msg::color(discard.color())).to_uppercase())
+                                    .heading()
+                                    .strong(),
+                            );
+                        });
+                    });
+                });
             });
         });
 
+        if let Some((from, to)) = drag {
+            hand::move_card(&mut my_hand.0, from, to);
+            *hand_sort = SortMode::Custom;
+            if *selected == Some(from) {
+                *selected = Some(to);
+            }
+        }
+
+        let card = play.and_then(|idx| my_hand.0.get(idx).copied());
+        if let Some(c) = card.filter(|c| my_turn && c.is_valid_on(discard)) {
+            if c == Card::PlusFour(Color::None) {
+                *choosing_wild = Some(c);
+                *choosing_p4 = true;
+            } else if c == Card::Wild(Color::None) {
+                *choosing_wild = Some(c);
+                *choosing_p4 = false;
+            } else if let (Some(game_id), Some(player_id)) = (*game_id, *player_id) {
+                let _ = tx.try_send(Client::PlaceCard {
+                    game_id,
+                    player_id,
+                    card: c,
+                    color: None,
+                    hand_version: *hand_version,
+                    version: *game_version,
+                });
+            }
+        }
+
+        animations.paint(ctx, *card_style);
         egui::Context::request_repaint(ctx);
     }
 }
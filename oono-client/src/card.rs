@@ -1,4 +1,4 @@
-use std::{cmp::Ordering, fmt::Display, mem::discriminant};
+use std::{cmp::Ordering, fmt::Display, mem::discriminant, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 
@@ -12,6 +12,14 @@ pub enum Color {
     None,
 }
 impl Color {
+    pub const ALL: [Color; 5] = [
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::None,
+    ];
+
     fn number(self) -> u8 {
         match self {
             Color::Red => 0,
@@ -41,13 +49,13 @@ impl Display for Color {
 
 impl PartialOrd for Color {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.number().partial_cmp(&other.number())
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for Color {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
+        self.number().cmp(&other.number())
     }
 }
 
@@ -63,27 +71,23 @@ pub enum Card {
 
 impl Ord for Card {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
-    }
-}
-
-impl PartialOrd for Card {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         //if discriminant(self) == discriminant(&Card::PlusFour(Color::None)) {
-        //    return Some(Ordering::Greater);
+        //    return Ordering::Greater;
         //}
         if self.color() == other.color() {
-            if self.number() == other.number() {
-                Some(Ordering::Equal)
-            } else {
-                self.number().partial_cmp(&other.number())
-            }
+            self.number().cmp(&other.number())
         } else {
-            self.color().partial_cmp(&other.color())
+            self.color().cmp(&other.color())
         }
     }
 }
 
+impl PartialOrd for Card {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Card {
     #[must_use]
     pub fn color(&self) -> Color {
@@ -97,7 +101,9 @@ impl Card {
         }
     }
 
-    fn number(self) -> u8 {
+    /// The number printed on the card, with action cards numbered after the 9.
+    #[must_use]
+    pub fn number(self) -> u8 {
         match self {
             Card::Number(n, _) => n,
             Card::PlusTwo(_) => 10,
@@ -114,6 +120,62 @@ impl Card {
             || discriminant(self) == discriminant(&Card::PlusFour(Color::None))
             || discriminant(self) == discriminant(&Card::Wild(Color::None))
     }
+
+    /// How cards travel in URLs: the variant, the number for number cards, then the color,
+    /// e.g. `Number-7-Red`, `Skip-Blue` or `Wild-None`.
+    #[must_use]
+    pub fn code(&self) -> String {
+        let color = self.color();
+        match self {
+            Card::Number(n, _) => format!("Number-{}-{:?}", n, color),
+            Card::PlusTwo(_) => format!("PlusTwo-{:?}", color),
+            Card::Reverse(_) => format!("Reverse-{:?}", color),
+            Card::Skip(_) => format!("Skip-{:?}", color),
+            Card::Wild(_) => format!("Wild-{:?}", color),
+            Card::PlusFour(_) => format!("PlusFour-{:?}", color),
+        }
+    }
+}
+
+/// A string that isn't the `Card::code` of any card.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidCardCode(pub String);
+
+impl Display for InvalidCardCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "not a card: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidCardCode {}
+
+impl FromStr for Card {
+    type Err = InvalidCardCode;
+
+    /// Parses `Card::code`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidCardCode(s.to_string());
+        let (kind, color) = s.rsplit_once('-').ok_or_else(invalid)?;
+        let color = Color::ALL
+            .into_iter()
+            .find(|c| format!("{:?}", c) == color)
+            .ok_or_else(invalid)?;
+        match kind.split_once('-') {
+            Some(("Number", n)) => match n.parse() {
+                Ok(n) if n <= 9 => Ok(Card::Number(n, color)),
+                _ => Err(invalid()),
+            },
+            None => match kind {
+                "PlusTwo" => Ok(Card::PlusTwo(color)),
+                "Reverse" => Ok(Card::Reverse(color)),
+                "Skip" => Ok(Card::Skip(color)),
+                "Wild" => Ok(Card::Wild(color)),
+                "PlusFour" => Ok(Card::PlusFour(color)),
+                _ => Err(invalid()),
+            },
+            _ => Err(invalid()),
+        }
+    }
 }
 
 impl Display for Card {
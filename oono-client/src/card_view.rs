@@ -0,0 +1,545 @@
+use std::f32::consts::TAU;
+
+use egui::{
+    color::Color32, emath::Rot2, pos2, vec2, Align2, FontId, Painter, Pos2, Rect, Response, Sense,
+    Shape, Stroke, Ui, Vec2, Widget,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    card::{Card, Color},
+    i18n::{Language, Msg},
+};
+
+/// Width of a card, as a fraction of its height.
+pub const ASPECT: f32 = 0.66;
+/// How far a hovered card rises, as a fraction of its height.
+const LIFT: f32 = 0.1;
+/// The oval in the middle of a card leans this far to the right.
+const TILT: f32 = TAU / 12.0;
+/// At most this many backs are drawn for the pot.
+const STACK_DEPTH: usize = 3;
+
+/// The colors cards are drawn in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Palette {
+    #[default]
+    Standard,
+    /// Safe for red-green color blindness, from the Okabe-Ito palette.
+    Deuteranopia,
+    /// Keeps red away from dark tones, which protanopes can't tell apart.
+    Protanopia,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 3] = [
+        Palette::Standard,
+        Palette::Deuteranopia,
+        Palette::Protanopia,
+    ];
+
+    #[must_use]
+    pub fn name(self) -> Msg {
+        match self {
+            Palette::Standard => Msg::PaletteStandard,
+            Palette::Deuteranopia => Msg::PaletteDeuteranopia,
+            Palette::Protanopia => Msg::PaletteProtanopia,
+        }
+    }
+}
+
+/// How cards are drawn. Set in the accessibility settings and saved with the app.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CardStyle {
+    pub palette: Palette,
+    /// Marks every color with its own shape, so color isn't the only way to tell them apart.
+    pub shapes: bool,
+}
+
+impl CardStyle {
+    /// The fill used for `color`.
+    #[must_use]
+    pub fn fill(self, color: Color) -> Color32 {
+        let (r, g, b) = match (self.palette, color) {
+            (_, Color::None) => (30, 30, 30),
+            (Palette::Standard, Color::Red) => (230, 30, 30),
+            (Palette::Standard, Color::Green) => (40, 190, 60),
+            (Palette::Standard, Color::Yellow) => (250, 210, 0),
+            (Palette::Standard, Color::Blue) => (0, 110, 230),
+            (Palette::Deuteranopia, Color::Red) => (213, 94, 0),
+            (Palette::Deuteranopia, Color::Green) => (0, 158, 115),
+            (Palette::Deuteranopia, Color::Yellow) => (240, 228, 66),
+            (Palette::Deuteranopia, Color::Blue) => (0, 114, 178),
+            (Palette::Protanopia, Color::Red) => (220, 38, 127),
+            (Palette::Protanopia, Color::Green) => (0, 158, 115),
+            (Palette::Protanopia, Color::Yellow) => (255, 176, 0),
+            (Palette::Protanopia, Color::Blue) => (100, 143, 255),
+        };
+        Color32::from_rgb(r, g, b)
+    }
+}
+
+/// A card painted by hand: face up, or as the face down pot.
+pub struct CardView {
+    card: Option<Card>,
+    height: f32,
+    playable: bool,
+    interactive: bool,
+    selected: bool,
+    hinted: bool,
+    draggable: bool,
+    stack: usize,
+    style: CardStyle,
+}
+
+impl CardView {
+    /// The face of `card`.
+    #[must_use]
+    pub fn new(card: Card) -> Self {
+        Self {
+            card: Some(card),
+            height: 90.,
+            playable: true,
+            interactive: true,
+            selected: false,
+            hinted: false,
+            draggable: false,
+            stack: 1,
+            style: CardStyle::default(),
+        }
+    }
+
+    /// A stack of `cards` face down cards. An empty stack is drawn as an outline.
+    #[must_use]
+    pub fn pot(cards: usize) -> Self {
+        Self {
+            card: None,
+            stack: cards,
+            ..Self::new(Card::Wild(Color::None))
+        }
+    }
+
+    #[must_use]
+    pub fn height(mut self, height: f32) -> Self {
+        self.height = height;
+        self
+    }
+
+    /// Cards that can't be played are greyed out and can't be clicked.
+    #[must_use]
+    pub fn playable(mut self, playable: bool) -> Self {
+        self.playable = playable;
+        self
+    }
+
+    /// Whether the card reacts to the mouse at all. Off for the discard.
+    #[must_use]
+    pub fn interactive(mut self, interactive: bool) -> Self {
+        self.interactive = interactive;
+        self
+    }
+
+    /// Picked with the keyboard. Raised and outlined like a hovered card.
+    #[must_use]
+    pub fn selected(mut self, selected: bool) -> Self {
+        self.selected = selected;
+        self
+    }
+
+    /// The suggested move. Outlined in gold.
+    #[must_use]
+    pub fn hinted(mut self, hinted: bool) -> Self {
+        self.hinted = hinted;
+        self
+    }
+
+    /// Can be dragged around, whether or not it can be played.
+    #[must_use]
+    pub fn draggable(mut self, draggable: bool) -> Self {
+        self.draggable = draggable;
+        self
+    }
+
+    #[must_use]
+    pub fn style(mut self, style: CardStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    fn size(&self) -> Vec2 {
+        vec2(self.height * ASPECT, self.height)
+    }
+}
+
+impl Widget for CardView {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let size = self.size();
+        let depth = self.stack.clamp(1, STACK_DEPTH) as f32 - 1.;
+        let offset = vec2(size.x * 0.04, -size.y * 0.03);
+        let room = vec2(offset.x * depth, (size.y * LIFT).max(-offset.y * depth));
+        let clickable = self.interactive && self.playable;
+        let sense = match (clickable, self.draggable) {
+            (true, true) => Sense::click_and_drag(),
+            (true, false) => Sense::click(),
+            (false, true) => Sense::drag(),
+            (false, false) => Sense::hover(),
+        };
+        let (rect, response) = ui.allocate_exact_size(size + room, sense);
+        if !ui.is_rect_visible(rect) {
+            return response;
+        }
+
+        let lift = ui.ctx().animate_bool(
+            response.id,
+            self.selected || clickable && response.hovered() || response.dragged(),
+        );
+        let card = Rect::from_min_size(rect.left_bottom() - vec2(0., size.y), size)
+            .translate(vec2(0., -lift * size.y * LIFT));
+        let painter = ui.painter();
+
+        match self.card {
+            Some(c) => paint_face(painter, card, c, self.playable, self.style),
+            None if self.stack == 0 => {
+                painter.rect_stroke(
+                    card,
+                    card.width() * 0.1,
+                    Stroke::new(2., Color32::from_gray(90)),
+                );
+            }
+            None => {
+                for i in 0..=depth as usize {
+                    paint_back(painter, card.translate(offset * i as f32), self.style);
+                }
+            }
+        }
+        if self.selected {
+            painter.rect_stroke(
+                card.expand(2.5),
+                card.width() * 0.1,
+                Stroke::new(3., ui.visuals().selection.stroke.color),
+            );
+        } else if self.hinted {
+            painter.rect_stroke(
+                card.expand(2.5),
+                card.width() * 0.1,
+                Stroke::new(3., Color32::GOLD),
+            );
+        } else if clickable && response.hovered() {
+            painter.rect_stroke(
+                card.expand(1.5),
+                card.width() * 0.1,
+                ui.visuals().widgets.hovered.fg_stroke,
+            );
+        }
+        response
+    }
+}
+
+/// Paints `card` filling `rect`, or a card back for `None`. For drawing cards outside of
+/// the layout, like the ones flying across the table.
+pub fn paint(painter: &Painter, rect: Rect, card: Option<Card>, style: CardStyle) {
+    match card {
+        Some(c) => paint_face(painter, rect, c, true, style),
+        None => paint_back(painter, rect, style),
+    }
+}
+
+/// A button for picking `color` in the wild color picker: a swatch with the color's shape
+/// and its name underneath.
+pub struct Swatch {
+    color: Color,
+    style: CardStyle,
+    language: Language,
+}
+
+impl Swatch {
+    #[must_use]
+    pub fn new(color: Color, style: CardStyle, language: Language) -> Self {
+        Self {
+            color,
+            style,
+            language,
+        }
+    }
+}
+
+impl Widget for Swatch {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let size = vec2(70., 70.);
+        let (rect, response) = ui.allocate_exact_size(size, Sense::click());
+        if !ui.is_rect_visible(rect) {
+            return response;
+        }
+        let painter = ui.painter();
+        let swatch = Rect::from_min_size(rect.min, vec2(size.x, size.y * 0.65));
+        painter.rect_filled(swatch, 6., self.style.fill(self.color));
+        if self.style.shapes {
+            paint_shape(
+                painter,
+                self.color,
+                swatch.center(),
+                swatch.height() * 0.6,
+                Color32::WHITE,
+            );
+        }
+        let stroke = match response.hovered() {
+            true => ui.visuals().widgets.hovered.fg_stroke,
+            false => ui.visuals().widgets.inactive.bg_stroke,
+        };
+        painter.rect_stroke(swatch, 6., stroke);
+        painter.text(
+            pos2(rect.center().x, swatch.bottom() + 4.),
+            Align2::CENTER_TOP,
+            self.language.text(Msg::color(self.color)).to_uppercase(),
+            FontId::proportional(18.),
+            ui.visuals().strong_text_color(),
+        );
+        response
+    }
+}
+
+/// A small card for the move history: the card's color with its index on it. Wilds show the
+/// color that was picked.
+pub struct Chip {
+    card: Card,
+    style: CardStyle,
+}
+
+impl Chip {
+    #[must_use]
+    pub fn new(card: Card, style: CardStyle) -> Self {
+        Self { card, style }
+    }
+}
+
+impl Widget for Chip {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let (rect, response) = ui.allocate_exact_size(vec2(22., 30.), Sense::hover());
+        if !ui.is_rect_visible(rect) {
+            return response;
+        }
+        let painter = ui.painter();
+        painter.rect_filled(rect, 4., self.style.fill(self.card.color()));
+        paint_symbol(
+            painter,
+            self.card,
+            rect.center(),
+            rect.height() * 0.55,
+            Color32::WHITE,
+            self.style,
+        );
+        response
+    }
+}
+
+/// Washes `color` out towards grey, for cards that can't be played.
+fn greyed(color: Color32, playable: bool) -> Color32 {
+    if playable {
+        return color;
+    }
+    let grey = 110.;
+    let mix = |c: u8| (c as f32 * 0.3 + grey * 0.7) as u8;
+    Color32::from_rgb(mix(color.r()), mix(color.g()), mix(color.b()))
+}
+
+fn paint_face(painter: &Painter, rect: Rect, card: Card, playable: bool, style: CardStyle) {
+    let white = greyed(Color32::WHITE, playable);
+    let body = greyed(style.fill(card.color()), playable);
+    let rounding = rect.width() * 0.1;
+
+    painter.rect_filled(rect, rounding, white);
+    let inner = rect.shrink(rect.width() * 0.06);
+    painter.rect_filled(inner, rounding * 0.7, body);
+
+    let center = inner.center();
+    let radii = vec2(inner.width() * 0.42, inner.height() * 0.36);
+    match card {
+        Card::Wild(_) | Card::PlusFour(_) => {
+            let quadrants = [Color::Red, Color::Blue, Color::Yellow, Color::Green];
+            for (i, c) in quadrants.into_iter().enumerate() {
+                let from = i as f32 * TAU / 4.;
+                let mut points = vec![center];
+                points.extend(oval(center, radii, from, from + TAU / 4., 10));
+                painter.add(Shape::convex_polygon(
+                    points,
+                    greyed(style.fill(c), playable),
+                    Stroke::none(),
+                ));
+                if style.shapes {
+                    let a = from + TAU / 8.;
+                    let middle =
+                        Rot2::from_angle(TILT) * vec2(radii.x * a.cos(), radii.y * a.sin()) * 0.55;
+                    paint_shape(painter, c, center + middle, radii.x * 0.35, white);
+                }
+            }
+            painter.add(Shape::closed_line(
+                oval(center, radii, 0., TAU, 40),
+                Stroke::new(rect.width() * 0.03, white),
+            ));
+        }
+        _ => {
+            painter.add(Shape::convex_polygon(
+                oval(center, radii, 0., TAU, 40),
+                white,
+                Stroke::none(),
+            ));
+        }
+    }
+
+    let symbol = match card {
+        Card::Wild(_) => None,
+        Card::PlusFour(_) => Some(white),
+        _ => Some(body),
+    };
+    if let Some(color) = symbol {
+        paint_symbol(painter, card, center, inner.height() * 0.42, color, style);
+    }
+
+    // corner indices, top left and bottom right
+    let corner = inner.height() * 0.16;
+    let inset = vec2(corner * 0.7, corner * 0.7);
+    paint_symbol(
+        painter,
+        card,
+        inner.left_top() + inset,
+        corner,
+        white,
+        style,
+    );
+    paint_symbol(
+        painter,
+        card,
+        inner.right_bottom() - inset,
+        corner,
+        white,
+        style,
+    );
+
+    // the free corners get the color's shape
+    if style.shapes && card.color() != Color::None {
+        let other = vec2(inset.x, -inset.y);
+        paint_shape(
+            painter,
+            card.color(),
+            inner.right_top() - other,
+            corner * 0.8,
+            white,
+        );
+        paint_shape(
+            painter,
+            card.color(),
+            inner.left_bottom() + other,
+            corner * 0.8,
+            white,
+        );
+    }
+}
+
+fn paint_back(painter: &Painter, rect: Rect, style: CardStyle) {
+    let rounding = rect.width() * 0.1;
+    painter.rect_filled(rect, rounding, Color32::WHITE);
+    let inner = rect.shrink(rect.width() * 0.06);
+    painter.rect_filled(inner, rounding * 0.7, style.fill(Color::None));
+    let center = inner.center();
+    let radii = vec2(inner.width() * 0.42, inner.height() * 0.36);
+    painter.add(Shape::convex_polygon(
+        oval(center, radii, 0., TAU, 40),
+        style.fill(Color::Red),
+        Stroke::none(),
+    ));
+    painter.text(
+        center,
+        Align2::CENTER_CENTER,
+        "OONO",
+        FontId::proportional(inner.height() * 0.17),
+        style.fill(Color::Yellow),
+    );
+}
+
+/// Draws the index of `card` (its number, or a symbol for the action cards) centered on
+/// `center`, about `size` tall.
+fn paint_symbol(
+    painter: &Painter,
+    card: Card,
+    center: Pos2,
+    size: f32,
+    color: Color32,
+    style: CardStyle,
+) {
+    let stroke = Stroke::new(size * 0.1, color);
+    let text = |t: &str| {
+        painter.text(
+            center,
+            Align2::CENTER_CENTER,
+            t,
+            FontId::proportional(size),
+            color,
+        );
+    };
+    match card {
+        Card::Number(n, _) => text(&n.to_string()),
+        Card::PlusTwo(_) => text("+2"),
+        Card::PlusFour(_) => text("+4"),
+        Card::Skip(_) => {
+            let r = size * 0.35;
+            painter.circle_stroke(center, r, stroke);
+            let slash = Rot2::from_angle(TAU / 8.) * vec2(r, 0.);
+            painter.line_segment([center - slash, center + slash], stroke);
+        }
+        Card::Reverse(_) => {
+            let r = size * 0.35;
+            let along = Rot2::from_angle(-TAU / 8.) * vec2(r * 2., 0.);
+            let apart = Rot2::from_angle(TAU / 8.) * vec2(r * 0.35, 0.);
+            painter.arrow(center - along / 2. - apart, along, stroke);
+            painter.arrow(center + along / 2. + apart, -along, stroke);
+        }
+        Card::Wild(_) => {
+            // four dots in the wild colors
+            let r = size * 0.18;
+            let colors = [Color::Red, Color::Blue, Color::Yellow, Color::Green];
+            for (i, c) in colors.into_iter().enumerate() {
+                let at = Rot2::from_angle(TAU / 8. + i as f32 * TAU / 4.) * vec2(r * 1.3, 0.);
+                painter.circle(center + at, r, style.fill(c), Stroke::new(1., color));
+            }
+        }
+    }
+}
+
+/// Paints the shape that stands for `color`, about `size` across: a triangle for red, a
+/// square for green, a circle for yellow and a diamond for blue.
+fn paint_shape(painter: &Painter, color: Color, center: Pos2, size: f32, fill: Color32) {
+    let r = size / 2.;
+    let outline = Stroke::new(1., Color32::BLACK);
+    let corners = |n: usize, start: f32| -> Vec<Pos2> {
+        (0..n)
+            .map(|i| center + Rot2::from_angle(start + i as f32 * TAU / n as f32) * vec2(r, 0.))
+            .collect()
+    };
+    match color {
+        Color::Red => {
+            painter.add(Shape::convex_polygon(corners(3, -TAU / 4.), fill, outline));
+        }
+        Color::Green => {
+            let square = Rect::from_center_size(center, vec2(size, size) * 0.75);
+            painter.rect(square, 0., fill, outline);
+        }
+        Color::Yellow => painter.circle(center, r * 0.85, fill, outline),
+        Color::Blue => {
+            painter.add(Shape::convex_polygon(corners(4, 0.), fill, outline));
+        }
+        Color::None => {}
+    }
+}
+
+/// Points along the tilted oval around `center`, from angle `from` to `to`.
+fn oval(center: Pos2, radii: Vec2, from: f32, to: f32, steps: usize) -> Vec<Pos2> {
+    let tilt = Rot2::from_angle(TILT);
+    (0..=steps)
+        .map(|i| {
+            let a = from + (to - from) * i as f32 / steps as f32;
+            center + tilt * vec2(radii.x * a.cos(), radii.y * a.sin())
+        })
+        .collect()
+}
@@ -0,0 +1,81 @@
+use std::{collections::VecDeque, fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// Longest chat message the server accepts, in characters.
+pub const MAX_MESSAGE_LEN: usize = 200;
+/// How many messages a game keeps around.
+pub const HISTORY_LEN: usize = 50;
+/// How long an emote stays next to the sender's name, in seconds.
+pub const EMOTE_SECS: u64 = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ChatMessage {
+    /// Who sent it. Seats move up when somebody leaves, so older messages go by name.
+    pub name: String,
+    pub text: String,
+}
+
+/// Ring buffer of the most recent messages in a game.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ChatLog(pub VecDeque<ChatMessage>);
+
+impl ChatLog {
+    pub fn push(&mut self, message: ChatMessage) {
+        if self.0.len() >= HISTORY_LEN {
+            self.0.pop_front();
+        }
+        self.0.push_back(message);
+    }
+}
+
+/// One-click reactions. The server rejects anything that is not in this list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Emote {
+    Nice,
+    Ouch,
+    HurryUp,
+    ThumbsUp,
+    Oops,
+    GoodGame,
+}
+
+impl Emote {
+    pub const ALL: [Emote; 6] = [
+        Emote::Nice,
+        Emote::Ouch,
+        Emote::HurryUp,
+        Emote::ThumbsUp,
+        Emote::Oops,
+        Emote::GoodGame,
+    ];
+}
+
+impl Display for Emote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Emote::Nice => "Nice!",
+                Emote::Ouch => "Ouch",
+                Emote::HurryUp => "Hurry up",
+                Emote::ThumbsUp => "👍",
+                Emote::Oops => "Oops",
+                Emote::GoodGame => "GG",
+            }
+        )
+    }
+}
+
+/// Parses the variant name, which is how emotes travel in URLs.
+impl FromStr for Emote {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Emote::ALL
+            .into_iter()
+            .find(|e| format!("{:?}", e) == s)
+            .ok_or(())
+    }
+}
@@ -1,6 +1,9 @@
 use crate::card::{Card, Color};
 use rand::prelude::*;
 use serde::{Deserialize, Serialize};
+
+/// Number of cards in `Deck::new_full`.
+pub const DECK_SIZE: usize = 100;
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 
 pub struct Deck(pub Vec<Card>);
@@ -10,8 +13,10 @@ impl Deck {
         Self(vec![])
     }
 
+    /// A shuffled standard deck. Shuffling only uses `rng`, so a seeded rng always deals the
+    /// same deck.
     #[must_use]
-    pub fn new_full() -> Self {
+    pub fn new_full(rng: &mut impl Rng) -> Self {
         let mut arr = vec![];
         for c in [Color::Red, Color::Green, Color::Blue, Color::Yellow] {
             for n in 0..=9 {
@@ -27,8 +32,7 @@ impl Deck {
             arr.push(Card::Wild(Color::None));
         }
 
-        let mut rng = thread_rng();
-        arr.shuffle(&mut rng);
+        arr.shuffle(rng);
         Self(arr)
     }
 }
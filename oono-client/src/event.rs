@@ -1,10 +1,14 @@
-use std::fmt::Display;
+#[cfg(not(target_arch = "wasm32"))]
 use std::thread;
-use std::time::Duration;
+use std::{fmt::Display, time::Duration};
 use tokio_stream::StreamExt;
 
 use crate::card::{Card, Color};
+use crate::chat::{ChatMessage, Emote};
 use crate::deck::Deck;
+use crate::history::Move;
+use crate::i18n::{Language, Msg};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::{Receiver, Sender};
 
@@ -20,40 +24,74 @@ pub enum Client {
         game_id: Uuid,
         name: String,
     },
+    /// Joins with the short code from `GameCreated` instead of the game id.
+    JoinByCode {
+        code: String,
+        name: String,
+    },
     StartGame {
         game_id: Uuid,
         gm_token: Uuid,
+        version: u64,
+    },
+    SetHints {
+        game_id: Uuid,
+        gm_token: Uuid,
+        allowed: bool,
     },
+    /// Long-polls for the next update. `since` is the `seen_ret` of the last one, or 0 to get
+    /// everything.
     RequestUpdate {
         game_id: Uuid,
         player_id: Uuid,
+        since: u64,
     },
+    /// Plays `card` from the hand, wherever it is shown. `hand_version` is the one of the
+    /// update the card was picked from.
     PlaceCard {
         game_id: Uuid,
         player_id: Uuid,
-        index: usize,
+        card: Card,
         color: Option<Color>,
+        hand_version: u64,
+        /// The game version of that update, see `Game::version`.
+        version: u64,
     },
     DrawCard {
         game_id: Uuid,
         player_id: Uuid,
+        version: u64,
+    },
+    SendChat {
+        game_id: Uuid,
+        player_id: Uuid,
+        message: String,
+    },
+    SendEmote {
+        game_id: Uuid,
+        player_id: Uuid,
+        emote: Emote,
     },
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 
 pub struct OpaquePlayer {
     pub order_num: usize,
     pub hand_size: usize,
     pub name: String,
+    /// Only set while the emote is fresh.
+    pub emote: Option<Emote>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 
 pub enum Server {
     GameCreated {
         game_id_ret: Uuid,
         gm_token_ret: Uuid,
+        /// Short code other players can join with, see `join_code`.
+        join_code_ret: String,
     },
     GameJoined {
         game_id_ret: Uuid,
@@ -61,145 +99,483 @@ pub enum Server {
         order_num_ret: usize,
     },
     GameStarted,
+    HintsSet,
     UpdateResponse {
         playing_ret: bool,
         hand_ret: Deck,
+        /// See `Player::hand_version`.
+        hand_version_ret: u64,
+        /// See `Game::version`. Moves send it back, so they only apply to the table they
+        /// were made on.
+        version_ret: u64,
         discard_ret: Card,
         reversed_ret: bool,
         players_ret: Vec<OpaquePlayer>,
         whose_turn_ret: usize,
         pot_size_ret: usize,
+        announcement_ret: Option<String>,
+        chat_ret: Vec<ChatMessage>,
+        /// The most recent moves, oldest first.
+        moves_ret: Vec<Move>,
+        /// Whether the GM allows move hints in this game.
+        hints_ret: bool,
+    },
+    /// Answers a long-polling `RequestUpdate` with the fields that changed since the update
+    /// it named. Every field is set when the server doesn't know that one anymore.
+    UpdateChanged {
+        /// Goes into the next request's `since`.
+        seen_ret: u64,
+        /// How long the server held the request, so it isn't counted as latency.
+        held_ms_ret: u64,
+        delta: UpdateDelta,
     },
+    /// Nothing changed before the long poll timed out.
+    NoChange,
     CardPlaced,
     CardDrawn,
+    ChatSent,
+    EmoteSent,
     PlayerWon {
         order_num: usize,
     },
 }
-#[derive(Serialize, Deserialize)]
+/// The fields of an `UpdateResponse` that changed, each `None` when it didn't. Unchanged
+/// fields are left out of the JSON altogether.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct UpdateDelta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub playing_ret: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hand_ret: Option<Deck>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hand_version_ret: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_ret: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discard_ret: Option<Card>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reversed_ret: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub players_ret: Option<Vec<OpaquePlayer>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub whose_turn_ret: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pot_size_ret: Option<usize>,
+    /// `Some(None)` when the announcement was taken down, which is sent as `null`.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "present"
+    )]
+    pub announcement_ret: Option<Option<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chat_ret: Option<Vec<ChatMessage>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moves_ret: Option<Vec<Move>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hints_ret: Option<bool>,
+}
+
+/// A field that is there at all is `Some`, even when it is `null`.
+fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+impl UpdateDelta {
+    /// Every field of `update`, or nothing when it isn't an `UpdateResponse`.
+    #[must_use]
+    pub fn full(update: &Server) -> Self {
+        match update.clone() {
+            Server::UpdateResponse {
+                playing_ret,
+                hand_ret,
+                hand_version_ret,
+                version_ret,
+                discard_ret,
+                reversed_ret,
+                players_ret,
+                whose_turn_ret,
+                pot_size_ret,
+                announcement_ret,
+                chat_ret,
+                moves_ret,
+                hints_ret,
+            } => Self {
+                playing_ret: Some(playing_ret),
+                hand_ret: Some(hand_ret),
+                hand_version_ret: Some(hand_version_ret),
+                version_ret: Some(version_ret),
+                discard_ret: Some(discard_ret),
+                reversed_ret: Some(reversed_ret),
+                players_ret: Some(players_ret),
+                whose_turn_ret: Some(whose_turn_ret),
+                pot_size_ret: Some(pot_size_ret),
+                announcement_ret: Some(announcement_ret),
+                chat_ret: Some(chat_ret),
+                moves_ret: Some(moves_ret),
+                hints_ret: Some(hints_ret),
+            },
+            _ => Self::default(),
+        }
+    }
+
+    /// What changed from `old` to `new`. Without an `old` to compare with, all of `new` did.
+    #[must_use]
+    pub fn between(old: Option<&Server>, new: &Server) -> Self {
+        fn changed<T: PartialEq>(old: Option<T>, new: Option<T>) -> Option<T> {
+            if old == new {
+                None
+            } else {
+                new
+            }
+        }
+        let old = old.map(Self::full).unwrap_or_default();
+        let new = Self::full(new);
+        Self {
+            playing_ret: changed(old.playing_ret, new.playing_ret),
+            hand_ret: changed(old.hand_ret, new.hand_ret),
+            hand_version_ret: changed(old.hand_version_ret, new.hand_version_ret),
+            version_ret: changed(old.version_ret, new.version_ret),
+            discard_ret: changed(old.discard_ret, new.discard_ret),
+            reversed_ret: changed(old.reversed_ret, new.reversed_ret),
+            players_ret: changed(old.players_ret, new.players_ret),
+            whose_turn_ret: changed(old.whose_turn_ret, new.whose_turn_ret),
+            pot_size_ret: changed(old.pot_size_ret, new.pot_size_ret),
+            announcement_ret: changed(old.announcement_ret, new.announcement_ret),
+            chat_ret: changed(old.chat_ret, new.chat_ret),
+            moves_ret: changed(old.moves_ret, new.moves_ret),
+            hints_ret: changed(old.hints_ret, new.hints_ret),
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The whole update, with the fields that didn't change taken from `base`. `None` if a
+    /// field is in neither.
+    #[must_use]
+    pub fn apply(self, base: Option<&Server>) -> Option<Server> {
+        let base = base.map(Self::full).unwrap_or_default();
+        Some(Server::UpdateResponse {
+            playing_ret: self.playing_ret.or(base.playing_ret)?,
+            hand_ret: self.hand_ret.or(base.hand_ret)?,
+            hand_version_ret: self.hand_version_ret.or(base.hand_version_ret)?,
+            version_ret: self.version_ret.or(base.version_ret)?,
+            discard_ret: self.discard_ret.or(base.discard_ret)?,
+            reversed_ret: self.reversed_ret.or(base.reversed_ret)?,
+            players_ret: self.players_ret.or(base.players_ret)?,
+            whose_turn_ret: self.whose_turn_ret.or(base.whose_turn_ret)?,
+            pot_size_ret: self.pot_size_ret.or(base.pot_size_ret)?,
+            announcement_ret: self.announcement_ret.or(base.announcement_ret)?,
+            chat_ret: self.chat_ret.or(base.chat_ret)?,
+            moves_ret: self.moves_ret.or(base.moves_ret)?,
+            hints_ret: self.hints_ret.or(base.hints_ret)?,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
 
 pub enum Error {
-    CouldNotContactServer { url: String, error: String },
-    MalformedResponse { error: String },
-    InvalidUuid { id: String, error: String },
-    GameDoesNotExist { game_id: Uuid },
-    PlayerDoesNotExist { player_id: Uuid },
-    InvalidGMToken { bad_token: Uuid },
-    CardOutOfRange { index: usize },
+    CouldNotContactServer {
+        url: String,
+        error: String,
+    },
+    MalformedResponse {
+        error: String,
+    },
+    InvalidUuid {
+        id: String,
+        error: String,
+    },
+    GameDoesNotExist {
+        game_id: Uuid,
+    },
+    PlayerDoesNotExist {
+        player_id: Uuid,
+    },
+    InvalidGMToken {
+        bad_token: Uuid,
+    },
+    InvalidCard {
+        card: String,
+    },
+    /// The hand changed between picking a card and the move arriving, so it wasn't played.
+    StaleMove,
+    /// The table changed since the update the request was based on, so nothing was done.
+    StaleState,
+    /// Tried to play a card the player doesn't hold.
+    NotInHand {
+        card: Card,
+    },
     IllegalMove,
+    MessageTooLong {
+        max: usize,
+    },
+    RateLimited,
+    InvalidEmote {
+        emote: String,
+    },
+    SeedNotAllowed,
+    OutOfCards,
+    InvalidJoinCode {
+        code: String,
+    },
+    /// Only made by the client, when the server names a winner it never told us about.
+    UnknownWinner {
+        order_num: usize,
+    },
 }
 
-impl Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Error {
+    /// The message for this error in `lang`.
+    #[must_use]
+    pub fn localize(&self, lang: Language) -> String {
         match self {
-            Error::CouldNotContactServer { url, error } => write!(f, "Could not contact the server at {} . Check your internet connection and provide the admin with the following error message:\n{}",url, error),
-            Error::InvalidUuid { id, error } => write!(
-                f,
-                "{} is not a valid UUID.\nThe error generated was:\n{}",
-                id, error
-            ),
-            Error::GameDoesNotExist { game_id } => write!(f, "{} is not a valid game ID. Make sure you have the correct ID.", game_id),
-            Error::MalformedResponse { error } => write!(f, "The program recieved a malformed response from the server:\n{} ", error),
-            Error::PlayerDoesNotExist { player_id } => write!(f, "{} is not a valid player ID. Tell the admin about this.", player_id),
-            Error::InvalidGMToken { bad_token } => write!(f, "{} is not the correct GM token. Stop cheating!", bad_token),
-            Error::CardOutOfRange { index } => write!(f, "Card {} is out of range. Stop cheating!", index),
-            Error::IllegalMove => write!(f, "That move is illegal. Stop cheating!"),
+            Error::CouldNotContactServer { url, error } => {
+                lang.tr(Msg::CouldNotContactServer, &[url, error])
+            }
+            Error::InvalidUuid { id, error } => lang.tr(Msg::InvalidUuid, &[id, error]),
+            Error::GameDoesNotExist { game_id } => lang.tr(Msg::GameDoesNotExist, &[game_id]),
+            Error::MalformedResponse { error } => lang.tr(Msg::MalformedResponse, &[error]),
+            Error::PlayerDoesNotExist { player_id } => {
+                lang.tr(Msg::PlayerDoesNotExist, &[player_id])
+            }
+            Error::InvalidGMToken { bad_token } => lang.tr(Msg::InvalidGMToken, &[bad_token]),
+            Error::StaleMove => lang.tr(Msg::StaleMove, &[]),
+            Error::StaleState => lang.tr(Msg::StaleState, &[]),
+            Error::InvalidCard { card } => lang.tr(Msg::InvalidCard, &[card]),
+            Error::NotInHand { card } => {
+                let name = format!("{} {}", lang.text(Msg::color(card.color())), card);
+                lang.tr(Msg::NotInHand, &[&name.trim()])
+            }
+            Error::IllegalMove => lang.tr(Msg::IllegalMove, &[]),
+            Error::MessageTooLong { max } => lang.tr(Msg::MessageTooLong, &[max]),
+            Error::RateLimited => lang.tr(Msg::RateLimited, &[]),
+            Error::InvalidEmote { emote } => lang.tr(Msg::InvalidEmote, &[emote]),
+            Error::SeedNotAllowed => lang.tr(Msg::SeedNotAllowed, &[]),
+            Error::OutOfCards => lang.tr(Msg::OutOfCards, &[]),
+            Error::InvalidJoinCode { code } => lang.tr(Msg::InvalidJoinCode, &[code]),
+            Error::UnknownWinner { .. } => lang.tr(Msg::UnknownWinner, &[]),
         }
     }
 }
 
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.localize(Language::English))
+    }
+}
+
+/// Starts sending the client's requests to the server in the background, and passes every
+/// response back on `server_evt_sender`. The answers to `RequestUpdate` go to
+/// `poll_evt_sender` instead, so they can be told apart from the rest.
 pub fn handle_events(
     client_evt_reciever: Receiver<Client>,
     server_evt_sender: Sender<Result<Server, Error>>,
+    poll_evt_sender: Sender<Result<Server, Error>>,
     server_url: String,
 ) {
+    #[cfg(not(target_arch = "wasm32"))]
     thread::spawn(move || {
+        let client = reqwest::ClientBuilder::new()
+            .timeout(Duration::from_millis(450))
+            .build()
+            .unwrap();
         tokio::runtime::Builder::new_multi_thread()
             .enable_all()
             .build()
             .unwrap()
-            .block_on(async {
-                let mut server_url = server_url.clone();
-                let mut in_stream = ReceiverStream::new(client_evt_reciever);
-                let client = reqwest::ClientBuilder::new()
-                    .timeout(Duration::from_millis(450))
-                    .build()
-                    .unwrap();
-                loop {
-                    match in_stream.next().await {
-                        Some(evt) => {
-                            match client
-                                .get(match evt {
-                                    Client::UpdateServer { url } => {
-                                        server_url = url;
-                                        continue;
-                                    }
-                                    Client::CreateGame => {
-                                        format!("{}/CreateGame", &server_url)
-                                    }
-                                    Client::JoinGame { game_id, name } => {
-                                        format!("{}/JoinGame/{}/{}", &server_url, game_id, name)
-                                    }
-                                    Client::StartGame { game_id, gm_token } => {
-                                        format!(
-                                            "{}/StartGame/{}/{}",
-                                            &server_url, game_id, gm_token
-                                        )
-                                    }
-                                    Client::RequestUpdate { game_id, player_id } => format!(
-                                        "{}/RequestUpdate/{}/{}",
-                                        &server_url, game_id, player_id
-                                    ),
-                                    Client::PlaceCard {
-                                        game_id,
-                                        player_id,
-                                        index,
-                                        color,
-                                    } => format!(
-                                        "{}/PlaceCard/{}/{}/{}/{}",
-                                        &server_url,
-                                        game_id,
-                                        player_id,
-                                        index,
-                                        if let Some(c) = color {
-                                            c.to_string()
-                                        } else {
-                                            "None".to_string()
-                                        }
-                                    ),
-                                    Client::DrawCard { game_id, player_id } => format!(
-                                        "{}/DrawCard/{}/{}",
-                                        &server_url, game_id, player_id
-                                    ),
-                                })
-                                .send()
-                                .await
-                            {
-                                Ok(r) => match r.json::<Result<Server, Error>>().await {
-                                    Ok(r) => {
-                                        server_evt_sender.send(r).await;
-                                    }
-                                    Err(e) => {
-                                        server_evt_sender
-                                            .send(Err(Error::MalformedResponse {
-                                                error: e.to_string(),
-                                            }))
-                                            .await;
-                                    }
-                                },
-                                Err(e) => {
-                                    server_evt_sender
-                                        .send(Err(Error::CouldNotContactServer {
-                                            url: (&server_url).to_string(),
-                                            error: e.to_string(),
-                                        }))
-                                        .await;
-                                }
-                            }
-                        }
-                        None => (),
-                    }
-                }
+            .block_on(event_loop(
+                client_evt_reciever,
+                server_evt_sender,
+                poll_evt_sender,
+                server_url,
+                client,
+            ));
+    });
+
+    // The browser has no threads, so the loop runs on the page's own event loop, and reqwest
+    // sends through fetch. Fetch can't be given a timeout, the browser's own applies, except
+    // to long polls, see `event_loop`.
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_futures::spawn_local(event_loop(
+        client_evt_reciever,
+        server_evt_sender,
+        poll_evt_sender,
+        server_url,
+        reqwest::Client::new(),
+    ));
+}
+
+/// How long a long-polling `RequestUpdate` may take before the client gives up on it. Servers
+/// answer well before that, however they are configured.
+pub const LONG_POLL_TIMEOUT_SECS: u64 = 30;
+
+const LONG_POLL_TIMEOUT: Duration = Duration::from_secs(LONG_POLL_TIMEOUT_SECS);
+
+async fn event_loop(
+    client_evt_reciever: Receiver<Client>,
+    server_evt_sender: Sender<Result<Server, Error>>,
+    poll_evt_sender: Sender<Result<Server, Error>>,
+    mut server_url: String,
+    client: reqwest::Client,
+) {
+    let mut in_stream = ReceiverStream::new(client_evt_reciever);
+    while let Some(evt) = in_stream.next().await {
+        let long_poll = matches!(evt, Client::RequestUpdate { .. });
+        let request = client.get(match evt {
+            Client::UpdateServer { url } => {
+                server_url = url;
+                continue;
+            }
+            Client::CreateGame => {
+                format!("{}/CreateGame", &server_url)
+            }
+            Client::JoinGame { game_id, name } => {
+                format!("{}/JoinGame/{}/{}", &server_url, game_id, name)
+            }
+            Client::JoinByCode { code, name } => {
+                format!("{}/JoinByCode/{}/{}", &server_url, code, name)
+            }
+            Client::StartGame {
+                game_id,
+                gm_token,
+                version,
+            } => format!(
+                "{}/StartGame/{}/{}?version={}",
+                &server_url, game_id, gm_token, version
+            ),
+            Client::SetHints {
+                game_id,
+                gm_token,
+                allowed,
+            } => {
+                format!(
+                    "{}/SetHints/{}/{}/{}",
+                    &server_url, game_id, gm_token, allowed
+                )
+            }
+            Client::RequestUpdate {
+                game_id,
+                player_id,
+                since,
+            } => format!(
+                "{}/RequestUpdate/{}/{}?since={}",
+                &server_url, game_id, player_id, since
+            ),
+            Client::PlaceCard {
+                game_id,
+                player_id,
+                card,
+                color,
+                hand_version,
+                version,
+            } => format!(
+                "{}/PlaceCard/{}/{}/{}/{}?hand={}&version={}",
+                &server_url,
+                game_id,
+                player_id,
+                card.code(),
+                if let Some(c) = color {
+                    c.to_string()
+                } else {
+                    "None".to_string()
+                },
+                hand_version,
+                version
+            ),
+            Client::DrawCard {
+                game_id,
+                player_id,
+                version,
+            } => format!(
+                "{}/DrawCard/{}/{}?version={}",
+                &server_url, game_id, player_id, version
+            ),
+            Client::SendChat {
+                game_id,
+                player_id,
+                message,
+            } => format!(
+                "{}/SendChat/{}/{}/{}",
+                &server_url,
+                game_id,
+                player_id,
+                utf8_percent_encode(&message, NON_ALPHANUMERIC)
+            ),
+            Client::SendEmote {
+                game_id,
+                player_id,
+                emote,
+            } => format!(
+                "{}/SendEmote/{}/{}/{:?}",
+                &server_url, game_id, player_id, emote
+            ),
+        });
+        if long_poll {
+            // the server holds these, so they must not keep the moves behind them waiting
+            let poll_evt_sender = poll_evt_sender.clone();
+            let server_url = server_url.clone();
+            #[cfg(not(target_arch = "wasm32"))]
+            tokio::spawn(async move {
+                let polled = fetch(request.timeout(LONG_POLL_TIMEOUT), server_url).await;
+                let _ = poll_evt_sender.send(polled).await;
             });
+            // a fetch that never comes back would leave the client waiting on it for good
+            #[cfg(target_arch = "wasm32")]
+            wasm_bindgen_futures::spawn_local(async move {
+                use futures_util::future::{select, Either};
+
+                let fetched = Box::pin(fetch(request, server_url.clone()));
+                let polled = match select(fetched, Box::pin(sleep(LONG_POLL_TIMEOUT))).await {
+                    Either::Left((polled, _)) => polled,
+                    Either::Right(_) => Err(Error::CouldNotContactServer {
+                        url: server_url,
+                        error: "the update timed out".to_string(),
+                    }),
+                };
+                let _ = poll_evt_sender.send(polled).await;
+            });
+        } else {
+            let res = fetch(request, server_url.clone()).await;
+            let _ = server_evt_sender.send(res).await;
+        }
+    }
+}
+
+/// Sends `request` and returns the answer, or why there is none.
+async fn fetch(request: reqwest::RequestBuilder, server_url: String) -> Result<Server, Error> {
+    match request.send().await {
+        Ok(r) => match r.json::<Result<Server, Error>>().await {
+            Ok(r) => r,
+            Err(e) => Err(Error::MalformedResponse {
+                error: e.to_string(),
+            }),
+        },
+        Err(e) => Err(Error::CouldNotContactServer {
+            url: server_url,
+            error: e.to_string(),
+        }),
+    }
+}
+
+/// Waits `duration` on the browser's timer, the only one there is.
+#[cfg(target_arch = "wasm32")]
+async fn sleep(duration: Duration) {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        if let Some(window) = web_sys::window() {
+            let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                &resolve,
+                duration.as_millis() as i32,
+            );
+        }
     });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
 }
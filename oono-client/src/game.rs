@@ -1,12 +1,23 @@
-use std::{collections::HashMap, mem::discriminant};
+use std::{collections::HashMap, fmt, mem::discriminant, time::Instant};
 
 use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use tokio::sync::watch;
 use uuid::Uuid;
 
 use crate::{
     card::{Card, Color},
-    deck::Deck,
+    chat::{ChatLog, Emote},
+    deck::{Deck, DECK_SIZE},
+    event::Error,
+    history::{Move, MoveLog},
 };
+
+/// Cards dealt to every player when they join.
+pub const HAND_SIZE: usize = 7;
+/// A game gets one more full deck for every this many players.
+pub const PLAYERS_PER_DECK: usize = 6;
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct Game {
     pub pot: Deck,
@@ -17,9 +28,304 @@ pub struct Game {
     pub players: HashMap<Uuid, Player>,
     pub started: bool,
     pub whos_turn: usize,
+    pub last_activity: Instant,
+    pub chat: ChatLog,
+    pub moves: MoveLog,
+    /// Whether players may see move hints. The GM can turn them off.
+    pub hints: bool,
+    /// Goes up with every change to what can be played: joins, leaves, the start and moves.
+    /// Chat, emotes and the hint setting don't count, so they never get in the way of a move.
+    pub version: u64,
+    /// Goes up with every change anyone at the table can see, chat and emotes included.
+    /// Long-polling updates wait for it to move, see `touch`.
+    pub revision: Revision,
+    /// Every random choice in the game comes from here, so a game can be replayed from its seed.
+    pub seed: u64,
+    pub rng: ChaCha8Rng,
+    /// Full decks in play. Only grows before the game starts, see `ensure_decks_for`.
+    pub decks: usize,
 }
 
 impl Game {
+    /// A new game in the lobby. Every random choice is drawn from `seed`.
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut pot = Deck::new_full(&mut rng);
+        // a wild has no color to follow, so the first card turned over is the topmost other one
+        let first = pot
+            .0
+            .iter()
+            .rposition(|c| c.color() != Color::None)
+            .unwrap();
+        let discard = Deck(vec![pot.0.remove(first)]);
+        Self {
+            pot,
+            discard,
+            creator_token: Uuid::new_v4(),
+            reversed: false,
+            players: HashMap::new(),
+            started: false,
+            whos_turn: 0,
+            last_activity: Instant::now(),
+            chat: ChatLog::default(),
+            moves: MoveLog::default(),
+            hints: true,
+            version: 0,
+            revision: Revision::default(),
+            seed,
+            rng,
+            decks: 1,
+        }
+    }
+
+    /// Turns down a request made against an older `version` of the table than this one.
+    pub fn check_version(&self, seen: u64) -> Result<(), Error> {
+        if seen != self.version {
+            tracing::warn!(seen, version = self.version, "stale state");
+            return Err(Error::StaleState);
+        }
+        Ok(())
+    }
+
+    /// Turns down a move made against an older version of the player's hand. Every hand change
+    /// also moves the table on, so this goes first for a move to be told apart from a stale
+    /// table.
+    pub fn check_hand_version(&self, player_id: &Uuid, seen: u64) -> Result<(), Error> {
+        let current = match self.players.get(player_id) {
+            Some(p) => p.hand_version,
+            None => {
+                return Err(Error::PlayerDoesNotExist {
+                    player_id: *player_id,
+                })
+            }
+        };
+        if seen != current {
+            tracing::warn!(hand_version = seen, current, "stale move");
+            return Err(Error::StaleMove);
+        }
+        Ok(())
+    }
+
+    /// Seats a new player and deals them a hand. Returns their id and seat.
+    pub fn add_player(&mut self, name: String) -> Result<(Uuid, usize), Error> {
+        let order_num = self.players.len();
+        if !self.started {
+            self.ensure_decks_for(order_num + 1);
+        }
+        let mut hand = vec![];
+        for _ in 0..HAND_SIZE {
+            if let Some(c) = self.pop_pot() {
+                hand.push(c);
+            } else {
+                tracing::warn!("not enough cards left to deal a hand");
+                self.pot.0.append(&mut hand);
+                return Err(Error::OutOfCards);
+            }
+        }
+        hand.sort();
+
+        let player_id = Uuid::new_v4();
+        self.players.insert(
+            player_id,
+            Player {
+                name,
+                order_num,
+                hand: Deck(hand),
+                hand_version: 0,
+                emote: None,
+            },
+        );
+        self.version += 1;
+        self.check_card_supply();
+        Ok((player_id, order_num))
+    }
+
+    /// Starts the game with a random player, and returns their seat.
+    pub fn start(&mut self) -> Result<usize, Error> {
+        if self.players.is_empty() {
+            tracing::warn!("tried to start a game without players");
+            return Err(Error::IllegalMove);
+        }
+        if self.started {
+            tracing::warn!("tried to start a game that is already running");
+            return Err(Error::IllegalMove);
+        }
+        let player_count = self.players.len();
+        self.whos_turn = self.rng.gen_range(0..player_count);
+        self.started = true;
+        self.version += 1;
+        Ok(self.whos_turn)
+    }
+
+    /// The name of whoever sits at `seat`.
+    fn name_at(&self, seat: usize) -> String {
+        self.players
+            .values()
+            .find(|p| p.order_num == seat)
+            .map(|p| p.name.clone())
+            .unwrap_or_default()
+    }
+
+    /// The seat of the player who got rid of all their cards, if anyone has.
+    #[must_use]
+    pub fn winner(&self) -> Option<usize> {
+        self.players
+            .values()
+            .find(|p| p.hand.0.is_empty())
+            .map(|p| p.order_num)
+    }
+
+    /// Plays `card` from the player's hand. Wilds need a `color`. `hand_version` is the
+    /// version of the hand the player saw when they chose the card. Returns the player's
+    /// seat if that was their last card.
+    pub fn play_card(
+        &mut self,
+        player_id: &Uuid,
+        card: Card,
+        color: Option<Color>,
+        hand_version: u64,
+    ) -> Result<Option<usize>, Error> {
+        let whos_turn = self.whos_turn;
+        let in_play = self.started && self.winner().is_none();
+        let discard = *self.discard.0.last().unwrap();
+
+        self.check_hand_version(player_id, hand_version)?;
+        let player = self.players.get_mut(player_id).unwrap();
+
+        // copies of a card are all alike, so which one is played doesn't matter
+        let index = match player.hand.0.iter().position(|c| *c == card) {
+            Some(i) => i,
+            None => return Err(Error::NotInHand { card }),
+        };
+        let mut card = card;
+
+        if !in_play || !card.is_valid_on(&discard) || whos_turn != player.order_num {
+            tracing::warn!(?card, ?discard, whos_turn, in_play, "illegal move");
+            return Err(Error::IllegalMove);
+        }
+
+        if let Card::Wild(_) | Card::PlusFour(_) = card {
+            let chosen = match color {
+                Some(c) if c != Color::None => c,
+                _ => {
+                    tracing::warn!(?card, "no color chosen for a wild card");
+                    return Err(Error::IllegalMove);
+                }
+            };
+            card = match card {
+                Card::Wild(_) => Card::Wild(chosen),
+                _ => Card::PlusFour(chosen),
+            };
+        }
+
+        // the move is legal, only now may the card leave the hand
+        player.hand.0.remove(index);
+        player.hand_version += 1;
+        tracing::info!(?card, ?discard, "card played");
+        let order_num = player.order_num;
+        let name = player.name.clone();
+        self.moves.push(Move::Played { name, card });
+        self.version += 1;
+
+        if self.players[player_id].hand.0.is_empty() {
+            self.discard.0.push(card);
+            self.check_card_supply();
+            tracing::info!(order_num, "player won");
+            return Ok(Some(order_num));
+        }
+
+        match card {
+            Card::Number(_, _) | Card::Wild(_) => {}
+            Card::PlusTwo(_) => {
+                let victim = self.next_turn();
+                let drawn = self.deal_to_seat(victim, 2);
+                tracing::info!(victim, ?drawn, "victim draws two");
+                self.moves.push(Move::DrewPenalty {
+                    name: self.name_at(victim),
+                    count: drawn.len(),
+                });
+                self.increment_turn();
+            }
+            Card::Reverse(_) => {
+                self.reversed = !self.reversed;
+                tracing::info!(reversed = self.reversed, "direction reversed");
+                self.moves.push(Move::Reversed);
+            }
+            Card::Skip(_) => {
+                self.increment_turn();
+                tracing::info!(skipped = self.whos_turn, "player skipped");
+                self.moves.push(Move::Skipped {
+                    name: self.name_at(self.whos_turn),
+                });
+            }
+            Card::PlusFour(_) => {
+                let victim = self.next_turn();
+                let drawn = self.deal_to_seat(victim, 4);
+                tracing::info!(victim, ?drawn, "victim draws four");
+                self.moves.push(Move::DrewPenalty {
+                    name: self.name_at(victim),
+                    count: drawn.len(),
+                });
+                self.increment_turn();
+            }
+        }
+
+        self.discard.0.push(card);
+        self.increment_turn();
+        self.check_card_supply();
+        tracing::info!(?card, next_turn = self.whos_turn, "turn finished");
+        Ok(None)
+    }
+
+    /// Draws a card into the hand of the player whose turn it is.
+    pub fn draw_card(&mut self, player_id: &Uuid) -> Result<Card, Error> {
+        let order_num = match self.players.get(player_id) {
+            Some(p) => p.order_num,
+            None => {
+                return Err(Error::PlayerDoesNotExist {
+                    player_id: *player_id,
+                })
+            }
+        };
+
+        if !self.started || self.winner().is_some() || self.whos_turn != order_num {
+            tracing::warn!(whos_turn = self.whos_turn, "drew out of turn");
+            return Err(Error::IllegalMove);
+        }
+
+        match self.deal_to_seat(order_num, 1).first() {
+            Some(card) => {
+                tracing::info!(?card, "card drawn");
+                let name = self.name_at(order_num);
+                self.moves.push(Move::Drew { name });
+                self.version += 1;
+                self.check_card_supply();
+                Ok(*card)
+            }
+            None => {
+                tracing::warn!("pot and discard are empty");
+                Err(Error::OutOfCards)
+            }
+        }
+    }
+
+    /// Marks the game as active, for the admin overview, and wakes clients waiting for an
+    /// update. Called after every change that went through.
+    pub fn touch(&mut self) {
+        self.last_activity = Instant::now();
+        self.revision.bump();
+    }
+
+    /// The seat that plays after the current one.
+    #[must_use]
+    pub fn next_turn(&self) -> usize {
+        match self.reversed {
+            true => ((self.whos_turn + self.players.len()) - 1) % self.players.len(),
+            false => (self.whos_turn + 1) % self.players.len(),
+        }
+    }
+
     pub fn increment_turn(&mut self) {
         self.whos_turn = match self.reversed {
             true => ((self.whos_turn + self.players.len()) - 1) % self.players.len(),
@@ -27,15 +333,105 @@ impl Game {
         }
     }
 
-    pub fn pop_pot(&mut self) -> Card {
-        let last_card = *self.discard.0.last().unwrap();
+    /// Takes a player out of the game. Their cards go to the bottom of the pot, and everyone
+    /// after them moves up one seat so the turn order stays contiguous.
+    pub fn remove_player(&mut self, player_id: &Uuid) -> Option<Player> {
+        let mut player = self.players.remove(player_id)?;
+        self.version += 1;
+        self.pot.0.splice(0..0, player.hand.0.drain(..));
+        for p in self.players.values_mut() {
+            if p.order_num > player.order_num {
+                p.order_num -= 1;
+            }
+        }
+        let seats = self.players.len();
+        if self.whos_turn > player.order_num {
+            self.whos_turn -= 1;
+        } else if self.whos_turn == player.order_num && self.reversed && seats > 0 {
+            // the turn passes on to the seat before theirs, the one after needs no fix-up
+            self.whos_turn = (self.whos_turn + seats - 1) % seats;
+        }
+        if self.whos_turn >= seats {
+            self.whos_turn = 0;
+        }
+        Some(player)
+    }
+
+    /// Moves up to `count` cards from the pot into the hand of whoever sits at `seat`, and
+    /// returns them. Fewer cards are dealt once the pot and the discard run out, and none when
+    /// nobody sits at `seat`.
+    pub fn deal_to_seat(&mut self, seat: usize, count: usize) -> Vec<Card> {
+        let mut drawn = vec![];
+        for _ in 0..count {
+            match self.pop_pot() {
+                Some(c) => drawn.push(c),
+                None => break,
+            }
+        }
+        match self.players.values_mut().find(|p| p.order_num == seat) {
+            Some(p) => {
+                p.hand.0.extend_from_slice(&drawn);
+                p.hand.0.sort();
+                if !drawn.is_empty() {
+                    p.hand_version += 1;
+                }
+            }
+            None => {
+                // back on top in the order they came off
+                drawn.reverse();
+                self.pot.0.extend_from_slice(&drawn);
+                return vec![];
+            }
+        }
+        drawn
+    }
 
+    /// Total number of cards this game is played with.
+    #[must_use]
+    pub fn supply(&self) -> usize {
+        self.decks * DECK_SIZE
+    }
+
+    /// Cards currently in the pot, the discard and every hand.
+    #[must_use]
+    pub fn card_count(&self) -> usize {
+        self.pot.0.len()
+            + self.discard.0.len()
+            + self.players.values().map(|p| p.hand.0.len()).sum::<usize>()
+    }
+
+    /// Cards only ever move between the pot, the discard and the hands, so `card_count` must
+    /// always match `supply`. Panics in debug builds when it doesn't.
+    pub fn check_card_supply(&self) {
+        let count = self.card_count();
+        if count != self.supply() {
+            tracing::error!(count, supply = self.supply(), "cards were created or lost");
+            debug_assert_eq!(count, self.supply(), "cards were created or lost");
+        }
+    }
+
+    /// Shuffles another full deck into the pot until there is one for every
+    /// `PLAYERS_PER_DECK` players. Only meant for the lobby: once the game has started the
+    /// supply is fixed.
+    pub fn ensure_decks_for(&mut self, players: usize) {
+        while self.decks * PLAYERS_PER_DECK < players {
+            self.pot.0.append(&mut Deck::new_full(&mut self.rng).0);
+            self.pot.0.shuffle(&mut self.rng);
+            self.decks += 1;
+            tracing::debug!(decks = self.decks, players, "added a deck");
+        }
+    }
+
+    /// Draws the top card of the pot, reshuffling the discard (all but its top card) into the
+    /// pot when it runs dry. `None` when every other card is in someone's hand.
+    pub fn pop_pot(&mut self) -> Option<Card> {
         if self.pot.0.is_empty() {
             if self.discard.0.len() <= 1 {
-                self.discard.0.append(&mut Deck::new_full().0);
+                return None;
             }
+            let last_card = self.discard.0.pop().unwrap();
             self.pot.0.append(&mut self.discard.0);
-            self.pot.0.shuffle(&mut thread_rng());
+            self.pot.0.shuffle(&mut self.rng);
             self.pot.0.iter_mut().for_each(|c| {
                 if discriminant(c) == discriminant(&Card::Wild(Color::None)) {
                     *c = Card::Wild(Color::None);
@@ -44,8 +440,12 @@ impl Game {
                 }
             });
             self.discard.0.push(last_card);
+            tracing::debug!(
+                pot_size = self.pot.0.len(),
+                "reshuffled the discard into the pot"
+            );
         }
-        self.pot.0.pop().unwrap()
+        self.pot.0.pop()
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -55,4 +455,50 @@ pub struct Player {
     pub order_num: usize,
 
     pub hand: Deck,
+    /// Goes up every time the hand changes. Moves name the version they were made
+    /// against, so one made against a hand that has since changed can be turned down.
+    pub hand_version: u64,
+    /// The last emote this player sent, and when.
+    pub emote: Option<(Emote, Instant)>,
+}
+
+/// A counter that can be waited on: `subscribe` hands out receivers that wake up whenever it
+/// goes up.
+pub struct Revision(watch::Sender<u64>);
+
+impl Revision {
+    #[must_use]
+    pub fn get(&self) -> u64 {
+        *self.0.borrow()
+    }
+
+    pub fn bump(&mut self) {
+        self.0.send_modify(|r| *r += 1);
+    }
+
+    /// Wakes on every bump from now on. Dropping the game ends the wait as well.
+    #[must_use]
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.0.subscribe()
+    }
+}
+
+impl Default for Revision {
+    fn default() -> Self {
+        Self(watch::channel(0).0)
+    }
+}
+
+impl PartialEq for Revision {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl Eq for Revision {}
+
+impl fmt::Debug for Revision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Revision({})", self.get())
+    }
 }
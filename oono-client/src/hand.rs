@@ -0,0 +1,83 @@
+//! How my hand is laid out. Only the client cares: moves name the card, not where it sits.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{card::Card, i18n::Msg};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortMode {
+    /// Grouped by color, like the server deals them.
+    #[default]
+    Color,
+    Number,
+    /// Wilds and action cards first, then the numbers.
+    Kind,
+    /// The cards that can go on the discard first.
+    Playable,
+    /// Arranged by dragging. New cards go on the end.
+    Custom,
+}
+
+impl SortMode {
+    pub const ALL: [SortMode; 5] = [
+        SortMode::Color,
+        SortMode::Number,
+        SortMode::Kind,
+        SortMode::Playable,
+        SortMode::Custom,
+    ];
+
+    #[must_use]
+    pub fn name(self) -> Msg {
+        match self {
+            SortMode::Color => Msg::SortColor,
+            SortMode::Number => Msg::SortNumber,
+            SortMode::Kind => Msg::SortKind,
+            SortMode::Playable => Msg::SortPlayable,
+            SortMode::Custom => Msg::SortCustom,
+        }
+    }
+}
+
+/// `hand` as it should be shown. `shown` is how the hand was laid out until now, which
+/// `Custom` keeps as far as the cards are still there.
+#[must_use]
+pub fn arrange(mode: SortMode, mut hand: Vec<Card>, shown: &[Card], discard: &Card) -> Vec<Card> {
+    match mode {
+        SortMode::Color => hand.sort(),
+        SortMode::Number => hand.sort_by_key(|c| (c.number(), c.color())),
+        SortMode::Kind => hand.sort_by_key(|c| (kind_rank(c), *c)),
+        SortMode::Playable => hand.sort_by_key(|c| (!c.is_valid_on(discard), *c)),
+        SortMode::Custom => {
+            let mut kept = vec![];
+            for c in shown {
+                if let Some(i) = hand.iter().position(|h| h == c) {
+                    kept.push(hand.remove(i));
+                }
+            }
+            kept.append(&mut hand);
+            return kept;
+        }
+    }
+    hand
+}
+
+/// Moves the card at `from` to `to`, shifting the ones in between.
+pub fn move_card(hand: &mut Vec<Card>, from: usize, to: usize) {
+    if from < hand.len() && to < hand.len() {
+        let card = hand.remove(from);
+        hand.insert(to, card);
+    }
+}
+
+/// Wilds first, then the other action cards, then the numbers.
+fn kind_rank(card: &Card) -> u8 {
+    match card {
+        Card::PlusFour(_) => 0,
+        Card::Wild(_) => 1,
+        Card::PlusTwo(_) => 2,
+        Card::Skip(_) => 3,
+        Card::Reverse(_) => 4,
+        Card::Number(_, _) => 5,
+    }
+}
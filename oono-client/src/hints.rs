@@ -0,0 +1,117 @@
+//! Suggestions for which card to play, for players still learning the game.
+
+use crate::{
+    card::{Card, Color},
+    i18n::{Language, Msg},
+};
+
+/// The next player is a threat at this many cards or fewer.
+pub const THREAT_CARDS: usize = 2;
+
+const COLORS: [Color; 4] = [Color::Red, Color::Green, Color::Yellow, Color::Blue];
+
+/// Why a card was suggested. Shown next to the highlighted card.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    /// The next player is about to go out, and this keeps them from playing or grows
+    /// their hand.
+    SlowDown { cards: usize },
+    /// Leaves `left` more cards of `color` to follow up with.
+    FollowUp { color: Color, left: usize },
+    /// Nothing is left in its color, so get rid of the high card while it's cheap.
+    HighCard,
+    /// Nothing else fits. Wilds are best kept for when that happens, then `color` is picked.
+    LastResort { color: Color },
+}
+
+impl Reason {
+    /// The reason in `lang`, as shown under the hand.
+    #[must_use]
+    pub fn localize(self, lang: Language) -> String {
+        let reason = match self {
+            Reason::SlowDown { cards } => lang.tr(Msg::HintSlowDown, &[&cards]),
+            Reason::FollowUp { color, left } => {
+                lang.tr(Msg::HintFollowUp, &[&left, &lang.text(Msg::color(color))])
+            }
+            Reason::HighCard => lang.tr(Msg::HintHighCard, &[]),
+            Reason::LastResort { color } => {
+                lang.tr(Msg::HintLastResort, &[&lang.text(Msg::color(color))])
+            }
+        };
+        lang.tr(Msg::Hint, &[&reason])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hint {
+    /// Index into the hand.
+    pub index: usize,
+    pub score: i32,
+    pub reason: Reason,
+}
+
+/// Every legal card in `hand`, best first. `next_hand_size` is the hand size of whoever plays
+/// after us, if known.
+#[must_use]
+pub fn rank(hand: &[Card], discard: &Card, next_hand_size: Option<usize>) -> Vec<Hint> {
+    let threat = next_hand_size.filter(|&n| n <= THREAT_CARDS);
+    let count = |color| hand.iter().filter(|c| c.color() == color).count();
+
+    let mut hints: Vec<_> = hand
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| c.is_valid_on(discard))
+        .map(|(index, card)| {
+            // wilds have no color to follow up in, and aren't follow-ups for each other
+            let left = match card.color() {
+                Color::None => 0,
+                color => count(color).saturating_sub(1),
+            };
+            let follow_up = Reason::FollowUp {
+                color: card.color(),
+                left,
+            };
+            let (score, reason) = match (*card, threat) {
+                // the +2 does nearly as much, and the +4 is worth keeping for later
+                (Card::PlusTwo(_), Some(cards)) => (40, Reason::SlowDown { cards }),
+                (Card::PlusFour(_), Some(cards)) => (35, Reason::SlowDown { cards }),
+                (Card::Skip(_), Some(cards)) => (30, Reason::SlowDown { cards }),
+                (Card::Reverse(_), Some(cards)) => (15, Reason::SlowDown { cards }),
+                (Card::Wild(_), _) | (Card::PlusFour(_), None) => (
+                    -20,
+                    Reason::LastResort {
+                        color: wild_color(hand, discard),
+                    },
+                ),
+                (Card::Number(n, _), _) if left == 0 => (i32::from(n) / 2, Reason::HighCard),
+                (Card::Number(n, _), _) => (i32::from(n) / 2, follow_up),
+                // action cards are worth the most points, so play them while it's cheap to
+                _ => (5, follow_up),
+            };
+            Hint {
+                index,
+                score: score + 3 * left as i32,
+                reason,
+            }
+        })
+        .collect();
+    hints.sort_by_key(|h| -h.score);
+    hints
+}
+
+/// The suggestion to highlight, if any card can be played.
+#[must_use]
+pub fn best(hand: &[Card], discard: &Card, next_hand_size: Option<usize>) -> Option<Hint> {
+    rank(hand, discard, next_hand_size).into_iter().next()
+}
+
+/// The color to pick for a wild: the one most of the rest of the hand is in.
+#[must_use]
+pub fn wild_color(hand: &[Card], discard: &Card) -> Color {
+    COLORS
+        .into_iter()
+        .map(|color| (hand.iter().filter(|c| c.color() == color).count(), color))
+        .filter(|(n, _)| *n > 0)
+        .max_by_key(|(n, color)| (*n, *color == discard.color()))
+        .map_or(discard.color(), |(_, color)| color)
+}
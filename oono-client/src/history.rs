@@ -0,0 +1,46 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::card::Card;
+
+/// How many moves a game keeps around.
+pub const MOVES_KEPT: usize = 30;
+
+/// Something that happened at the table, as shown in the history panel. Players go by name,
+/// since seats move up when somebody leaves.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Move {
+    /// `card` was played. Wilds carry the color that was picked.
+    Played {
+        name: String,
+        card: Card,
+    },
+    /// Drew a card on their turn. Which one stays secret.
+    Drew {
+        name: String,
+    },
+    /// Had to draw `count` cards for a +2 or +4, and lost their turn.
+    DrewPenalty {
+        name: String,
+        count: usize,
+    },
+    Skipped {
+        name: String,
+    },
+    /// Play goes the other way round now.
+    Reversed,
+}
+
+/// Ring buffer of the most recent moves in a game.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct MoveLog(pub VecDeque<Move>);
+
+impl MoveLog {
+    pub fn push(&mut self, m: Move) {
+        if self.0.len() >= MOVES_KEPT {
+            self.0.pop_front();
+        }
+        self.0.push_back(m);
+    }
+}
@@ -0,0 +1,450 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{card::Color, chat::Emote};
+
+/// Languages the client can be shown in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Spanish];
+
+    /// The name of the language, in itself.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Spanish => "Español",
+        }
+    }
+
+    /// `msg` in this language, with every `{}` filled in by the next of `args`.
+    #[must_use]
+    pub fn tr(self, msg: Msg, args: &[&dyn Display]) -> String {
+        let mut args = args.iter();
+        let mut parts = self.text(msg).split("{}");
+        let mut out = parts.next().unwrap_or_default().to_string();
+        for part in parts {
+            if let Some(arg) = args.next() {
+                out.push_str(&arg.to_string());
+            }
+            out.push_str(part);
+        }
+        out
+    }
+
+    /// The text of `msg` in this language, with its `{}` left in.
+    #[must_use]
+    pub fn text(self, msg: Msg) -> &'static str {
+        match self {
+            Language::English => english(msg),
+            Language::Spanish => spanish(msg),
+        }
+    }
+}
+
+/// Every bit of text the client shows. Each language has the whole list, which the compiler
+/// checks, so adding a message means translating it everywhere.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Msg {
+    // menus
+    File,
+    Quit,
+    Accessibility,
+    ColorShapes,
+    CardColors,
+    PaletteStandard,
+    PaletteDeuteranopia,
+    PaletteProtanopia,
+    Language,
+    Help,
+    KeyboardControls,
+    ShowHints,
+    Settings,
+    SavedServers,
+    RecentServers,
+    Nickname,
+    Latency,
+    Forget,
+
+    // joining
+    Welcome,
+    CreateGame,
+    Create,
+    JoinGame,
+    Join,
+    GameId,
+    GameIdOrCode,
+    JoinCode,
+    Name,
+    Server,
+    GmPanel,
+    StartGame,
+    AllowHints,
+    Winner,
+    HasWon,
+    NewGame,
+
+    // the table
+    YouAre,
+    Opponents,
+    WhoseTurn,
+    LastCard,
+    CardCount,
+    PlayingNow,
+    PlayingNext,
+    Chat,
+    SaySomething,
+    Send,
+    ServerMessage,
+    Close,
+    Error,
+    SelectColor,
+    ColorKeys,
+    MyHand,
+    SortBy,
+    SortColor,
+    SortNumber,
+    SortKind,
+    SortPlayable,
+    SortCustom,
+    DragToArrange,
+    Pot,
+    DrawACard,
+    DrawCard,
+    PotRemaining,
+    Discard,
+    NoColor,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    EmoteNice,
+    EmoteOuch,
+    EmoteHurryUp,
+    EmoteThumbsUp,
+    EmoteOops,
+    EmoteGoodGame,
+    History,
+    MovePlayed,
+    MoveDrew,
+    MoveDrewPenalty,
+    MoveSkipped,
+    MoveReversed,
+    Hint,
+    HintSlowDown,
+    HintFollowUp,
+    HintHighCard,
+    HintLastResort,
+
+    // keyboard
+    SelectLeft,
+    SelectRight,
+    PlaySelected,
+    PickRed,
+    PickGreen,
+    PickYellow,
+    PickBlue,
+    CancelWild,
+    ToggleHelp,
+    PressAKey,
+    ClickToRebind,
+    ResetDefaults,
+
+    // errors
+    CouldNotContactServer,
+    MalformedResponse,
+    InvalidUuid,
+    GameDoesNotExist,
+    PlayerDoesNotExist,
+    InvalidGMToken,
+    StaleMove,
+    StaleState,
+    InvalidCard,
+    NotInHand,
+    IllegalMove,
+    MessageTooLong,
+    RateLimited,
+    InvalidEmote,
+    SeedNotAllowed,
+    OutOfCards,
+    InvalidJoinCode,
+    UnknownWinner,
+}
+
+impl Msg {
+    #[must_use]
+    pub fn color(color: Color) -> Msg {
+        match color {
+            Color::Red => Msg::Red,
+            Color::Green => Msg::Green,
+            Color::Yellow => Msg::Yellow,
+            Color::Blue => Msg::Blue,
+            Color::None => Msg::NoColor,
+        }
+    }
+
+    #[must_use]
+    pub fn emote(emote: Emote) -> Msg {
+        match emote {
+            Emote::Nice => Msg::EmoteNice,
+            Emote::Ouch => Msg::EmoteOuch,
+            Emote::HurryUp => Msg::EmoteHurryUp,
+            Emote::ThumbsUp => Msg::EmoteThumbsUp,
+            Emote::Oops => Msg::EmoteOops,
+            Emote::GoodGame => Msg::EmoteGoodGame,
+        }
+    }
+}
+
+fn english(msg: Msg) -> &'static str {
+    match msg {
+        Msg::File => "File",
+        Msg::Quit => "Quit",
+        Msg::Accessibility => "Accessibility",
+        Msg::ColorShapes => "Mark colors with shapes",
+        Msg::CardColors => "Card colors",
+        Msg::PaletteStandard => "Standard",
+        Msg::PaletteDeuteranopia => "Deuteranopia safe",
+        Msg::PaletteProtanopia => "Protanopia safe",
+        Msg::Language => "Language",
+        Msg::Help => "Help",
+        Msg::KeyboardControls => "Keyboard controls",
+        Msg::ShowHints => "Show move hints",
+        Msg::Settings => "Settings",
+        Msg::SavedServers => "Saved servers",
+        Msg::RecentServers => "Recent",
+        Msg::Nickname => "Nickname",
+        Msg::Latency => "Latency",
+        Msg::Forget => "Forget",
+
+        Msg::Welcome => "Welcome to Oono :)",
+        Msg::CreateGame => "Create game",
+        Msg::Create => "Create",
+        Msg::JoinGame => "Join game",
+        Msg::Join => "Join",
+        Msg::GameId => "Game ID",
+        Msg::GameIdOrCode => "Game ID or code",
+        Msg::JoinCode => "Join code",
+        Msg::Name => "Name",
+        Msg::Server => "Server",
+        Msg::GmPanel => "GM panel",
+        Msg::StartGame => "Start game",
+        Msg::AllowHints => "Allow move hints",
+        Msg::Winner => "Winner",
+        Msg::HasWon => "{} has won the game!",
+        Msg::NewGame => "New game",
+
+        Msg::YouAre => "You are {}",
+        Msg::Opponents => "Opponents",
+        Msg::WhoseTurn => "Whose turn: {}",
+        Msg::LastCard => "UNO CARDS!",
+        Msg::CardCount => "{} cards",
+        Msg::PlayingNow => "Playing now",
+        Msg::PlayingNext => "Playing next",
+        Msg::Chat => "Chat",
+        Msg::SaySomething => "Say something",
+        Msg::Send => "Send",
+        Msg::ServerMessage => "Message from the server",
+        Msg::Close => "close",
+        Msg::Error => "Error",
+        Msg::SelectColor => "Select a color",
+        Msg::ColorKeys => "Keys: {} {} {} {}, {} to cancel",
+        Msg::MyHand => "My Hand",
+        Msg::SortBy => "Sort by",
+        Msg::SortColor => "Color",
+        Msg::SortNumber => "Number",
+        Msg::SortKind => "Action cards first",
+        Msg::SortPlayable => "Playable first",
+        Msg::SortCustom => "My own order",
+        Msg::DragToArrange => "Drag cards to arrange them.",
+        Msg::Pot => "POT",
+        Msg::DrawACard => "Draw a card",
+        Msg::DrawCard => "Draw card",
+        Msg::PotRemaining => "{} cards remain in the pot.",
+        Msg::Discard => "DISCARD",
+        Msg::NoColor => "",
+        Msg::Red => "Red",
+        Msg::Green => "Green",
+        Msg::Yellow => "Yellow",
+        Msg::Blue => "Blue",
+        Msg::EmoteNice => "Nice!",
+        Msg::EmoteOuch => "Ouch",
+        Msg::EmoteHurryUp => "Hurry up",
+        Msg::EmoteThumbsUp => "👍",
+        Msg::EmoteOops => "Oops",
+        Msg::EmoteGoodGame => "GG",
+        Msg::History => "History",
+        Msg::MovePlayed => "{} played",
+        Msg::MoveDrew => "{} drew a card",
+        Msg::MoveDrewPenalty => "{} drew {} and was skipped",
+        Msg::MoveSkipped => "{} was skipped",
+        Msg::MoveReversed => "Direction reversed",
+        Msg::Hint => "Hint: {}",
+        Msg::HintSlowDown => "the next player only has {} cards left, slow them down.",
+        Msg::HintFollowUp => "you have {} more {} cards to follow it with.",
+        Msg::HintHighCard => "get rid of a high card while you can.",
+        Msg::HintLastResort => "nothing else fits, so pick {}.",
+
+        Msg::SelectLeft => "Select the card to the left",
+        Msg::SelectRight => "Select the card to the right",
+        Msg::PlaySelected => "Play the selected card",
+        Msg::PickRed => "Pick red for a wild",
+        Msg::PickGreen => "Pick green for a wild",
+        Msg::PickYellow => "Pick yellow for a wild",
+        Msg::PickBlue => "Pick blue for a wild",
+        Msg::CancelWild => "Cancel a wild",
+        Msg::ToggleHelp => "Show or hide the keyboard controls",
+        Msg::PressAKey => "press a key…",
+        Msg::ClickToRebind => "Click to rebind",
+        Msg::ResetDefaults => "Reset to defaults",
+
+        Msg::CouldNotContactServer => "Could not contact the server at {} . Check your internet connection and provide the admin with the following error message:\n{}",
+        Msg::MalformedResponse => "The program recieved a malformed response from the server:\n{} ",
+        Msg::InvalidUuid => "{} is not a valid UUID.\nThe error generated was:\n{}",
+        Msg::GameDoesNotExist => "{} is not a valid game ID. Make sure you have the correct ID.",
+        Msg::PlayerDoesNotExist => "{} is not a valid player ID. Tell the admin about this.",
+        Msg::InvalidGMToken => "{} is not the correct GM token. Stop cheating!",
+        Msg::StaleMove => "Your hand changed before that move arrived, so it was not played. Have another look and try again.",
+        Msg::StaleState => "The game moved on before that arrived, so it was not done. Have another look and try again.",
+        Msg::InvalidCard => "{} is not a card. Stop cheating!",
+        Msg::NotInHand => "You don't have a {} to play. Stop cheating!",
+        Msg::IllegalMove => "That move is illegal. Stop cheating!",
+        Msg::MessageTooLong => "Chat messages can be at most {} characters long.",
+        Msg::RateLimited => "You are sending messages too quickly. Slow down a bit.",
+        Msg::InvalidEmote => "{} is not an emote. Stop cheating!",
+        Msg::SeedNotAllowed => "Seeded games can only be created by the admin.",
+        Msg::OutOfCards => "There are no cards left to draw.",
+        Msg::InvalidJoinCode => "{} is not the ID or join code of a running game. Check it with the GM.",
+        Msg::UnknownWinner => "A nonexistant player has won the game. Please contact the administrator.",
+    }
+}
+
+fn spanish(msg: Msg) -> &'static str {
+    match msg {
+        Msg::File => "Archivo",
+        Msg::Quit => "Salir",
+        Msg::Accessibility => "Accesibilidad",
+        Msg::ColorShapes => "Marcar los colores con formas",
+        Msg::CardColors => "Colores de las cartas",
+        Msg::PaletteStandard => "Estándar",
+        Msg::PaletteDeuteranopia => "Apto para deuteranopía",
+        Msg::PaletteProtanopia => "Apto para protanopía",
+        Msg::Language => "Idioma",
+        Msg::Help => "Ayuda",
+        Msg::KeyboardControls => "Controles de teclado",
+        Msg::ShowHints => "Mostrar pistas de jugada",
+        Msg::Settings => "Ajustes",
+        Msg::SavedServers => "Servidores guardados",
+        Msg::RecentServers => "Recientes",
+        Msg::Nickname => "Apodo",
+        Msg::Latency => "Latencia",
+        Msg::Forget => "Olvidar",
+
+        Msg::Welcome => "Bienvenido a Oono :)",
+        Msg::CreateGame => "Crear partida",
+        Msg::Create => "Crear",
+        Msg::JoinGame => "Unirse a una partida",
+        Msg::Join => "Unirse",
+        Msg::GameId => "ID de partida",
+        Msg::GameIdOrCode => "ID o código de partida",
+        Msg::JoinCode => "Código para unirse",
+        Msg::Name => "Nombre",
+        Msg::Server => "Servidor",
+        Msg::GmPanel => "Panel del anfitrión",
+        Msg::StartGame => "Empezar partida",
+        Msg::AllowHints => "Permitir pistas de jugada",
+        Msg::Winner => "Ganador",
+        Msg::HasWon => "¡{} ha ganado la partida!",
+        Msg::NewGame => "Nueva partida",
+
+        Msg::YouAre => "Eres {}",
+        Msg::Opponents => "Rivales",
+        Msg::WhoseTurn => "Turno de: {}",
+        Msg::LastCard => "¡UNO!",
+        Msg::CardCount => "{} cartas",
+        Msg::PlayingNow => "Juega ahora",
+        Msg::PlayingNext => "Juega después",
+        Msg::Chat => "Chat",
+        Msg::SaySomething => "Di algo",
+        Msg::Send => "Enviar",
+        Msg::ServerMessage => "Mensaje del servidor",
+        Msg::Close => "cerrar",
+        Msg::Error => "Error",
+        Msg::SelectColor => "Elige un color",
+        Msg::ColorKeys => "Teclas: {} {} {} {}, {} para cancelar",
+        Msg::MyHand => "Mi mano",
+        Msg::SortBy => "Ordenar por",
+        Msg::SortColor => "Color",
+        Msg::SortNumber => "Número",
+        Msg::SortKind => "Cartas de acción primero",
+        Msg::SortPlayable => "Jugables primero",
+        Msg::SortCustom => "Mi propio orden",
+        Msg::DragToArrange => "Arrastra las cartas para ordenarlas.",
+        Msg::Pot => "MAZO",
+        Msg::DrawACard => "Roba una carta",
+        Msg::DrawCard => "Robar carta",
+        Msg::PotRemaining => "Quedan {} cartas en el mazo.",
+        Msg::Discard => "DESCARTE",
+        Msg::NoColor => "",
+        Msg::Red => "Rojo",
+        Msg::Green => "Verde",
+        Msg::Yellow => "Amarillo",
+        Msg::Blue => "Azul",
+        Msg::EmoteNice => "¡Bien!",
+        Msg::EmoteOuch => "¡Ay!",
+        Msg::EmoteHurryUp => "Date prisa",
+        Msg::EmoteThumbsUp => "👍",
+        Msg::EmoteOops => "Uy",
+        Msg::EmoteGoodGame => "GG",
+        Msg::History => "Historial",
+        Msg::MovePlayed => "{} jugó",
+        Msg::MoveDrew => "{} robó una carta",
+        Msg::MoveDrewPenalty => "{} robó {} y perdió el turno",
+        Msg::MoveSkipped => "{} perdió el turno",
+        Msg::MoveReversed => "Cambio de sentido",
+        Msg::Hint => "Pista: {}",
+        Msg::HintSlowDown => "al siguiente jugador solo le quedan {} cartas, frénalo.",
+        Msg::HintFollowUp => "te quedan {} cartas más de color {} para seguir.",
+        Msg::HintHighCard => "quítate de encima una carta alta mientras puedas.",
+        Msg::HintLastResort => "no puedes jugar otra cosa, así que elige {}.",
+
+        Msg::SelectLeft => "Seleccionar la carta de la izquierda",
+        Msg::SelectRight => "Seleccionar la carta de la derecha",
+        Msg::PlaySelected => "Jugar la carta seleccionada",
+        Msg::PickRed => "Elegir rojo para un comodín",
+        Msg::PickGreen => "Elegir verde para un comodín",
+        Msg::PickYellow => "Elegir amarillo para un comodín",
+        Msg::PickBlue => "Elegir azul para un comodín",
+        Msg::CancelWild => "Cancelar un comodín",
+        Msg::ToggleHelp => "Mostrar u ocultar los controles de teclado",
+        Msg::PressAKey => "pulsa una tecla…",
+        Msg::ClickToRebind => "Haz clic para cambiar la tecla",
+        Msg::ResetDefaults => "Restablecer",
+
+        Msg::CouldNotContactServer => "No se pudo contactar con el servidor en {} . Comprueba tu conexión a internet y pasa al administrador este mensaje de error:\n{}",
+        Msg::MalformedResponse => "El programa recibió una respuesta mal formada del servidor:\n{} ",
+        Msg::InvalidUuid => "{} no es un UUID válido.\nEl error generado fue:\n{}",
+        Msg::GameDoesNotExist => "{} no es un ID de partida válido. Asegúrate de tener el ID correcto.",
+        Msg::PlayerDoesNotExist => "{} no es un ID de jugador válido. Avisa al administrador.",
+        Msg::InvalidGMToken => "{} no es el token de anfitrión correcto. ¡Deja de hacer trampas!",
+        Msg::StaleMove => "Tu mano cambió antes de que llegara la jugada, así que no se jugó. Vuelve a mirarla e inténtalo de nuevo.",
+        Msg::StaleState => "La partida siguió antes de que llegara, así que no se hizo. Vuelve a mirar e inténtalo de nuevo.",
+        Msg::InvalidCard => "{} no es una carta. ¡Deja de hacer trampas!",
+        Msg::NotInHand => "No tienes ningún {} que jugar. ¡Deja de hacer trampas!",
+        Msg::IllegalMove => "Esa jugada no está permitida. ¡Deja de hacer trampas!",
+        Msg::MessageTooLong => "Los mensajes del chat pueden tener como mucho {} caracteres.",
+        Msg::RateLimited => "Estás enviando mensajes demasiado rápido. Ve un poco más despacio.",
+        Msg::InvalidEmote => "{} no es un emote. ¡Deja de hacer trampas!",
+        Msg::SeedNotAllowed => "Solo el administrador puede crear partidas con semilla.",
+        Msg::OutOfCards => "No quedan cartas para robar.",
+        Msg::InvalidJoinCode => "{} no es el ID ni el código de una partida en curso. Compruébalo con el anfitrión.",
+        Msg::UnknownWinner => "Ha ganado un jugador que no existe. Contacta con el administrador.",
+    }
+}
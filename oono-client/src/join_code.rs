@@ -0,0 +1,28 @@
+//! Short codes for joining a game, easy to read out across the room.
+
+use rand::Rng;
+
+/// Characters in a join code.
+pub const CODE_LEN: usize = 6;
+/// Digits and capitals, without the ones that are easily mixed up: 0 and O, 1, I and L.
+pub const ALPHABET: &[u8] = b"23456789ABCDEFGHJKMNPQRSTUVWXYZ";
+
+/// A random code. The server only ever passes an OS seeded rng, so codes can't be guessed
+/// from a game's seed.
+pub fn generate(rng: &mut impl Rng) -> String {
+    (0..CODE_LEN)
+        .map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())] as char)
+        .collect()
+}
+
+/// The code in `input`, ignoring case, spaces and dashes. `None` if it isn't one.
+#[must_use]
+pub fn normalize(input: &str) -> Option<String> {
+    let code: String = input
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let valid = code.len() == CODE_LEN && code.bytes().all(|b| ALPHABET.contains(&b));
+    valid.then_some(code)
+}
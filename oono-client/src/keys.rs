@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use egui::{Event, InputState, Key};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::Msg;
+
+/// Everything that can be done from the keyboard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Action {
+    SelectLeft,
+    SelectRight,
+    Play,
+    Draw,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Cancel,
+    Help,
+}
+
+impl Action {
+    pub const ALL: [Action; 10] = [
+        Action::SelectLeft,
+        Action::SelectRight,
+        Action::Play,
+        Action::Draw,
+        Action::Red,
+        Action::Green,
+        Action::Yellow,
+        Action::Blue,
+        Action::Cancel,
+        Action::Help,
+    ];
+
+    /// What the action does, for the keyboard controls window.
+    #[must_use]
+    pub fn describe(self) -> Msg {
+        match self {
+            Action::SelectLeft => Msg::SelectLeft,
+            Action::SelectRight => Msg::SelectRight,
+            Action::Play => Msg::PlaySelected,
+            Action::Draw => Msg::DrawACard,
+            Action::Red => Msg::PickRed,
+            Action::Green => Msg::PickGreen,
+            Action::Yellow => Msg::PickYellow,
+            Action::Blue => Msg::PickBlue,
+            Action::Cancel => Msg::CancelWild,
+            Action::Help => Msg::ToggleHelp,
+        }
+    }
+
+    fn default_key(self) -> Key {
+        match self {
+            Action::SelectLeft => Key::ArrowLeft,
+            Action::SelectRight => Key::ArrowRight,
+            Action::Play => Key::Enter,
+            Action::Draw => Key::D,
+            Action::Red => Key::R,
+            Action::Green => Key::G,
+            Action::Yellow => Key::Y,
+            Action::Blue => Key::B,
+            Action::Cancel => Key::Escape,
+            Action::Help => Key::H,
+        }
+    }
+}
+
+/// Which key does what. Saved with the app, so rebound keys stick.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    /// Only the keys that were rebound, the rest use their defaults.
+    rebound: BTreeMap<Action, Key>,
+}
+
+impl KeyBindings {
+    #[must_use]
+    pub fn key(&self, action: Action) -> Key {
+        self.rebound
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| action.default_key())
+    }
+
+    /// Binds `key` to `action`. Whatever had `key` before takes over the old key of `action`,
+    /// so no two actions ever share a key.
+    pub fn rebind(&mut self, action: Action, key: Key) {
+        let old = self.key(action);
+        if let Some(other) = Action::ALL.into_iter().find(|a| self.key(*a) == key) {
+            self.set(other, old);
+        }
+        self.set(action, key);
+    }
+
+    pub fn reset(&mut self) {
+        self.rebound.clear();
+    }
+
+    /// Whether the key for `action` went down this frame, without any modifiers held.
+    #[must_use]
+    pub fn pressed(&self, input: &InputState, action: Action) -> bool {
+        input.modifiers.is_none() && input.key_pressed(self.key(action))
+    }
+
+    fn set(&mut self, action: Action, key: Key) {
+        match key == action.default_key() {
+            true => self.rebound.remove(&action),
+            false => self.rebound.insert(action, key),
+        };
+    }
+}
+
+/// The first key that went down this frame, for rebinding.
+#[must_use]
+pub fn first_pressed(input: &InputState) -> Option<Key> {
+    input.events.iter().find_map(|e| match e {
+        Event::Key {
+            key, pressed: true, ..
+        } => Some(*key),
+        _ => None,
+    })
+}
+
+/// A short name for `key`, as printed on the keyboard.
+#[must_use]
+pub fn key_name(key: Key) -> String {
+    let name = match key {
+        Key::Escape => "Esc".to_string(),
+        k => format!("{:?}", k),
+    };
+    ["Arrow", "Num"]
+        .iter()
+        .fold(name, |n, p| n.strip_prefix(p).unwrap_or(&n).to_string())
+}
@@ -1,4 +1,10 @@
 pub mod card;
+pub mod chat;
 pub mod deck;
 pub mod event;
 pub mod game;
+pub mod hand;
+pub mod hints;
+pub mod history;
+pub mod i18n;
+pub mod join_code;
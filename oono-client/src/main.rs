@@ -1,10 +1,20 @@
 #![warn(clippy::all, rust_2018_idioms)]
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
+pub mod anim;
 pub mod app;
 pub mod card;
+pub mod card_view;
+pub mod chat;
 pub mod deck;
 pub mod event;
 pub mod game;
+pub mod hand;
+pub mod hints;
+pub mod history;
+pub mod i18n;
+pub mod join_code;
+pub mod keys;
+pub mod servers;
 use app::OonoApp;
 // When compiling natively:
 #[cfg(not(target_arch = "wasm32"))]
@@ -19,3 +29,17 @@ fn main() {
         Box::new(|cc| Box::new(crate::OonoApp::new(cc))),
     );
 }
+
+// When compiling to the web, with trunk:
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    // Send panics and logs to the browser console.
+    console_error_panic_hook::set_once();
+    tracing_wasm::set_as_global_default();
+
+    eframe::start_web(
+        "the_canvas_id",
+        Box::new(|cc| Box::new(crate::OonoApp::new(cc))),
+    )
+    .expect("failed to start eframe");
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+/// How many servers are remembered.
+pub const SERVERS_KEPT: usize = 8;
+
+/// A server the player has played on.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedServer {
+    pub url: String,
+    /// Set in the settings window. Shown instead of the url when not empty.
+    pub nickname: String,
+    /// Round trip of the last update from this server, in milliseconds.
+    pub latency_ms: Option<u32>,
+}
+
+impl SavedServer {
+    #[must_use]
+    pub fn label(&self) -> &str {
+        match self.nickname.trim() {
+            "" => &self.url,
+            _ => &self.nickname,
+        }
+    }
+}
+
+/// Servers the player has played on, most recently used first. Saved with the app.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerList(pub Vec<SavedServer>);
+
+impl ServerList {
+    /// Moves `url` to the front, adding it if it's new. The least recently used server is
+    /// forgotten when there are too many.
+    pub fn used(&mut self, url: &str) {
+        let server = match self.0.iter().position(|s| s.url == url) {
+            Some(i) => self.0.remove(i),
+            None => SavedServer {
+                url: url.to_string(),
+                nickname: String::new(),
+                latency_ms: None,
+            },
+        };
+        self.0.insert(0, server);
+        self.0.truncate(SERVERS_KEPT);
+    }
+
+    pub fn record_latency(&mut self, url: &str, latency_ms: u32) {
+        if let Some(s) = self.0.iter_mut().find(|s| s.url == url) {
+            s.latency_ms = Some(latency_ms);
+        }
+    }
+}
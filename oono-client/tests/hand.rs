@@ -0,0 +1,79 @@
+//! Checks hands can be laid out any way without losing track of the cards.
+
+use oono::{
+    card::{Card, Color, InvalidCardCode},
+    deck::Deck,
+    hand::{self, SortMode},
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+#[test]
+fn every_card_survives_its_url_code() {
+    let deck = Deck::new_full(&mut ChaCha8Rng::seed_from_u64(0));
+    for card in deck.0.into_iter().chain([Card::Wild(Color::Red)]) {
+        assert_eq!(card.code().parse::<Card>(), Ok(card));
+    }
+    for bad in [
+        "",
+        "Wild",
+        "Number-10-Red",
+        "Number-Red",
+        "Skip-Purple",
+        "Skip-3-Red",
+    ] {
+        assert_eq!(
+            bad.parse::<Card>(),
+            Err(InvalidCardCode(bad.to_string())),
+            "{}",
+            bad
+        );
+    }
+}
+
+#[test]
+fn sort_modes_keep_the_same_cards() {
+    let dealt = Deck::new_full(&mut ChaCha8Rng::seed_from_u64(1)).0[..12].to_vec();
+    let discard = Card::Number(4, Color::Blue);
+    let mut sorted = dealt.clone();
+    sorted.sort();
+    for mode in SortMode::ALL {
+        let mut shown = hand::arrange(mode, dealt.clone(), &[], &discard);
+        shown.sort();
+        assert_eq!(shown, sorted, "{:?}", mode);
+    }
+
+    let playable = hand::arrange(SortMode::Playable, dealt, &[], &discard);
+    let split = playable.partition_point(|c| c.is_valid_on(&discard));
+    assert!(playable[split..].iter().all(|c| !c.is_valid_on(&discard)));
+}
+
+#[test]
+fn custom_order_keeps_dragged_cards_in_place() {
+    let mut shown = vec![
+        Card::Number(1, Color::Red),
+        Card::Skip(Color::Green),
+        Card::Number(1, Color::Red),
+        Card::Wild(Color::None),
+    ];
+    hand::move_card(&mut shown, 3, 0);
+    assert_eq!(shown[0], Card::Wild(Color::None));
+
+    // one red 1 was played and a blue 5 drawn, the server sends its own order
+    let from_server = vec![
+        Card::Number(1, Color::Red),
+        Card::Number(5, Color::Blue),
+        Card::Skip(Color::Green),
+        Card::Wild(Color::None),
+    ];
+    let discard = Card::Number(1, Color::Yellow);
+    assert_eq!(
+        hand::arrange(SortMode::Custom, from_server, &shown, &discard),
+        vec![
+            Card::Wild(Color::None),
+            Card::Number(1, Color::Red),
+            Card::Skip(Color::Green),
+            Card::Number(5, Color::Blue),
+        ]
+    );
+}
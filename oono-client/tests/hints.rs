@@ -0,0 +1,84 @@
+//! Checks the hints suggest what a seasoned player would.
+
+use oono::{
+    card::{Card, Color},
+    hints::{self, Reason},
+};
+
+#[test]
+fn only_legal_cards_are_suggested() {
+    let hand = [
+        Card::Number(3, Color::Red),
+        Card::Number(7, Color::Blue),
+        Card::Skip(Color::Green),
+        Card::Number(5, Color::Yellow),
+    ];
+    let ranked = hints::rank(&hand, &Card::Number(7, Color::Red), Some(5));
+    let mut legal: Vec<_> = ranked.iter().map(|h| h.index).collect();
+    legal.sort_unstable();
+    assert_eq!(legal, vec![0, 1]);
+
+    assert_eq!(
+        hints::best(&hand, &Card::Number(9, Color::Green), Some(5)).map(|h| h.index),
+        Some(2)
+    );
+    assert_eq!(
+        hints::best(
+            &[Card::Number(1, Color::Red)],
+            &Card::Skip(Color::Blue),
+            None
+        ),
+        None
+    );
+}
+
+#[test]
+fn wilds_are_kept_for_later() {
+    let hand = [
+        Card::Wild(Color::None),
+        Card::Number(2, Color::Blue),
+        Card::Number(4, Color::Red),
+        Card::Number(8, Color::Red),
+    ];
+    let best = hints::best(&hand, &Card::Number(4, Color::Blue), Some(6)).unwrap();
+    assert_eq!(best.index, 2);
+    assert_eq!(
+        best.reason,
+        Reason::FollowUp {
+            color: Color::Red,
+            left: 1
+        }
+    );
+
+    // with nothing else to play, pick the color most of the hand is in
+    let best = hints::best(&hand, &Card::Number(6, Color::Green), Some(6)).unwrap();
+    assert_eq!(best.index, 0);
+    assert_eq!(best.reason, Reason::LastResort { color: Color::Red });
+}
+
+#[test]
+fn next_player_about_to_win_is_slowed_down() {
+    let hand = [
+        Card::Number(9, Color::Yellow),
+        Card::Number(6, Color::Yellow),
+        Card::PlusTwo(Color::Yellow),
+        Card::PlusFour(Color::None),
+    ];
+    let discard = Card::Number(1, Color::Yellow);
+
+    let calm = hints::best(&hand, &discard, Some(7)).unwrap();
+    assert!(!matches!(calm.reason, Reason::SlowDown { .. }));
+
+    // the +2 does the job and keeps the +4 for later, even without yellows to follow it up
+    let best = hints::best(&hand, &discard, Some(1)).unwrap();
+    assert_eq!(best.index, 2);
+    assert_eq!(best.reason, Reason::SlowDown { cards: 1 });
+    let best = hints::best(&hand[2..], &discard, Some(1)).unwrap();
+    assert_eq!(best.index, 0);
+
+    // a plain wild doesn't slow anyone down, so it is still the last resort
+    let hand = [Card::Wild(Color::None), Card::Number(3, Color::Red)];
+    let best = hints::best(&hand, &discard, Some(1)).unwrap();
+    assert_eq!(best.index, 0);
+    assert_eq!(best.reason, Reason::LastResort { color: Color::Red });
+}
@@ -0,0 +1,108 @@
+//! Checks every error reads properly in every language.
+
+use oono::{
+    card::{Card, Color},
+    event::Error,
+    i18n::Language,
+};
+use uuid::Uuid;
+
+/// One of every error, with arguments that are easy to spot.
+fn every_error() -> Vec<(Error, Vec<String>)> {
+    let id = Uuid::new_v4();
+    vec![
+        (
+            Error::CouldNotContactServer {
+                url: "http://example.test".to_string(),
+                error: "timed out".to_string(),
+            },
+            vec!["http://example.test".to_string(), "timed out".to_string()],
+        ),
+        (
+            Error::MalformedResponse {
+                error: "bad json".to_string(),
+            },
+            vec!["bad json".to_string()],
+        ),
+        (
+            Error::InvalidUuid {
+                id: "not-a-uuid".to_string(),
+                error: "bad length".to_string(),
+            },
+            vec!["not-a-uuid".to_string(), "bad length".to_string()],
+        ),
+        (
+            Error::GameDoesNotExist { game_id: id },
+            vec![id.to_string()],
+        ),
+        (
+            Error::PlayerDoesNotExist { player_id: id },
+            vec![id.to_string()],
+        ),
+        (
+            Error::InvalidGMToken { bad_token: id },
+            vec![id.to_string()],
+        ),
+        (Error::StaleMove, vec![]),
+        (Error::StaleState, vec![]),
+        (
+            Error::InvalidCard {
+                card: "Number-12-Red".to_string(),
+            },
+            vec!["Number-12-Red".to_string()],
+        ),
+        (
+            Error::NotInHand {
+                card: Card::Number(7, Color::Red),
+            },
+            vec!["7".to_string()],
+        ),
+        (Error::IllegalMove, vec![]),
+        (
+            Error::MessageTooLong { max: 1234 },
+            vec!["1234".to_string()],
+        ),
+        (Error::RateLimited, vec![]),
+        (
+            Error::InvalidEmote {
+                emote: "Wave".to_string(),
+            },
+            vec!["Wave".to_string()],
+        ),
+        (Error::SeedNotAllowed, vec![]),
+        (Error::OutOfCards, vec![]),
+        (
+            Error::InvalidJoinCode {
+                code: "QX7-K2P".to_string(),
+            },
+            vec!["QX7-K2P".to_string()],
+        ),
+        (Error::UnknownWinner { order_num: 7 }, vec![]),
+    ]
+}
+
+#[test]
+fn errors_fill_in_their_arguments() {
+    for lang in Language::ALL {
+        for (error, args) in every_error() {
+            let text = error.localize(lang);
+            assert!(!text.contains("{}"), "{:?} in {:?}: {}", error, lang, text);
+            for arg in args {
+                assert!(text.contains(&arg), "{:?} in {:?}: {}", error, lang, text);
+            }
+        }
+    }
+}
+
+#[test]
+fn errors_are_translated() {
+    for (error, _) in every_error() {
+        assert_eq!(error.to_string(), error.localize(Language::English));
+        assert_ne!(
+            error.localize(Language::English),
+            error.localize(Language::Spanish),
+            "{:?}",
+            error
+        );
+    }
+}
@@ -0,0 +1,244 @@
+//! Plays lots of random games against `Game` and checks the rules hold after every move.
+
+use oono::{
+    card::{Card, Color},
+    event::Error,
+    game::Game,
+};
+use proptest::{collection::vec, prelude::*};
+use uuid::Uuid;
+
+const COLORS: [Color; 4] = [Color::Red, Color::Green, Color::Yellow, Color::Blue];
+
+/// A lobby with `players` seated players.
+fn game_with(seed: u64, players: usize) -> Game {
+    let mut game = Game::new(seed);
+    for i in 0..players {
+        game.add_player(format!("player {}", i)).unwrap();
+    }
+    game
+}
+
+fn at_seat(game: &Game, seat: usize) -> Uuid {
+    *game
+        .players
+        .iter()
+        .find(|(_, p)| p.order_num == seat)
+        .unwrap()
+        .0
+}
+
+/// Pot, discard, turn, direction and every hand by seat.
+type Snapshot = (Vec<Card>, Vec<Card>, usize, bool, Vec<(usize, Vec<Card>)>);
+
+/// Everything a rejected move must leave untouched.
+fn snapshot(game: &Game) -> Snapshot {
+    let mut hands: Vec<_> = game
+        .players
+        .values()
+        .map(|p| (p.order_num, p.hand.0.clone()))
+        .collect();
+    hands.sort();
+    (
+        game.pot.0.clone(),
+        game.discard.0.clone(),
+        game.whos_turn,
+        game.reversed,
+        hands,
+    )
+}
+
+fn check_invariants(game: &Game) -> Result<(), TestCaseError> {
+    let players = game.players.len();
+    prop_assert!(
+        game.whos_turn < players,
+        "turn {} of {}",
+        game.whos_turn,
+        players
+    );
+
+    let mut seats: Vec<_> = game.players.values().map(|p| p.order_num).collect();
+    seats.sort_unstable();
+    prop_assert_eq!(seats, (0..players).collect::<Vec<_>>());
+
+    prop_assert!(
+        game.discard.0.iter().all(|c| c.color() != Color::None),
+        "uncolored card on the discard: {:?}",
+        game.discard.0
+    );
+    prop_assert_eq!(game.card_count(), game.supply());
+    prop_assert!(
+        game.players
+            .values()
+            .filter(|p| p.hand.0.is_empty())
+            .count()
+            <= 1
+    );
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(128))]
+
+    #[test]
+    fn random_games_follow_the_rules(
+        seed in any::<u64>(),
+        players in 2..=10usize,
+        moves in vec(any::<u32>(), 1..600),
+    ) {
+        let mut game = game_with(seed, players);
+        check_invariants(&game)?;
+        game.start().unwrap();
+
+        for choice in moves {
+            let choice = choice as usize;
+            check_invariants(&game)?;
+
+            // now and then somebody leaves
+            if choice.is_multiple_of(31) && game.players.len() > 2 {
+                let leaving = at_seat(&game, choice % game.players.len());
+                game.remove_player(&leaving).unwrap();
+                continue;
+            }
+
+            // moving out of turn is rejected and changes nothing
+            let others = game.players.len() - 1;
+            let other = (game.whos_turn + 1 + choice % others) % game.players.len();
+            let other = at_seat(&game, other);
+            let before = snapshot(&game);
+            let theirs = game.players[&other].hand.0[0];
+            let version = game.players[&other].hand_version;
+            prop_assert!(matches!(game.play_card(&other, theirs, Some(Color::Red), version), Err(Error::IllegalMove)));
+            prop_assert!(matches!(game.draw_card(&other), Err(Error::IllegalMove)));
+            prop_assert_eq!(&before, &snapshot(&game));
+
+            // starting again would pick a new first player in the middle of the game
+            prop_assert!(matches!(game.start(), Err(Error::IllegalMove)));
+            prop_assert_eq!(&before, &snapshot(&game));
+
+            let current = at_seat(&game, game.whos_turn);
+            let top = *game.discard.0.last().unwrap();
+            let hand = &game.players[&current].hand.0;
+            let version = game.players[&current].hand_version;
+            let legal: Vec<_> = (0..hand.len()).filter(|&i| hand[i].is_valid_on(&top)).collect();
+
+            if let Some(&illegal) = (0..hand.len()).find(|i| !legal.contains(i)).as_ref() {
+                prop_assert!(matches!(game.play_card(&current, hand[illegal], Some(Color::Red), version), Err(Error::IllegalMove)));
+                prop_assert_eq!(&before, &snapshot(&game));
+            }
+            if let Some(missing) = missing_card(&game, &current) {
+                prop_assert!(matches!(
+                    game.play_card(&current, missing, None, version),
+                    Err(Error::NotInHand { .. })
+                ), "a card that isn't in the hand was played");
+            }
+
+            if legal.is_empty() {
+                match game.draw_card(&current) {
+                    Ok(_) => continue,
+                    // every card is in somebody's hand and nobody can move, call it a draw
+                    Err(Error::OutOfCards) => return Ok(()),
+                    Err(e) => return Err(TestCaseError::fail(format!("draw failed: {:?}", e))),
+                }
+            }
+
+            let card = game.players[&current].hand.0[legal[choice % legal.len()]];
+            if let Card::Wild(_) | Card::PlusFour(_) = card {
+                prop_assert!(matches!(game.play_card(&current, card, None, version), Err(Error::IllegalMove)));
+                prop_assert_eq!(&before, &snapshot(&game));
+            }
+            let color = Some(COLORS[(choice / 7) % COLORS.len()]);
+            // a move made against an older hand is turned down, even if the card is still there
+            let stale = version.wrapping_sub(1 + choice as u64 % 3);
+            prop_assert!(matches!(game.play_card(&current, card, color, stale), Err(Error::StaleMove)));
+            prop_assert_eq!(&before, &snapshot(&game));
+            let seat = game.whos_turn;
+            let table_version = game.version;
+            match game.play_card(&current, card, color, version) {
+                Ok(None) => {
+                    prop_assert_eq!(game.winner(), None);
+                    // anything based on the table before the move is now out of date
+                    prop_assert!(matches!(game.check_version(table_version), Err(Error::StaleState)));
+                    prop_assert!(game.check_version(game.version).is_ok());
+                }
+                Ok(Some(winner)) => {
+                    check_invariants(&game)?;
+                    prop_assert_eq!(winner, seat);
+                    prop_assert_eq!(game.winner(), Some(winner));
+
+                    // the game is over, nobody may move any more
+                    let after = snapshot(&game);
+                    for id in game.players.keys().copied().collect::<Vec<_>>() {
+                        let any = Card::Wild(Color::None);
+                        let version = game.players[&id].hand_version;
+                        prop_assert!(game.play_card(&id, any, Some(Color::Red), version).is_err());
+                        prop_assert!(game.draw_card(&id).is_err());
+                    }
+                    prop_assert_eq!(&after, &snapshot(&game));
+                    return Ok(());
+                }
+                Err(e) => return Err(TestCaseError::fail(format!("legal move rejected: {:?}", e))),
+            }
+        }
+        check_invariants(&game)?;
+    }
+
+    #[test]
+    fn turns_stay_in_range(
+        players in 1..=20usize,
+        start in any::<usize>(),
+        reversals in vec(any::<bool>(), 0..50),
+    ) {
+        let mut game = game_with(0, players);
+        game.whos_turn = start % players;
+        for reverse in reversals {
+            if reverse {
+                game.reversed = !game.reversed;
+            }
+            let next = game.next_turn();
+            prop_assert!(next < players);
+            game.increment_turn();
+            prop_assert_eq!(game.whos_turn, next);
+        }
+    }
+
+    #[test]
+    fn leaving_passes_the_turn_on_in_the_current_direction(
+        players in 3..=10usize,
+        start in any::<usize>(),
+        leaving in any::<usize>(),
+        reversed in any::<bool>(),
+    ) {
+        let mut game = game_with(0, players);
+        game.whos_turn = start % players;
+        game.reversed = reversed;
+        let leaving = at_seat(&game, leaving % players);
+        let current = at_seat(&game, game.whos_turn);
+        // whoever would have played next takes over from a player who leaves on their turn
+        let expected = match leaving == current {
+            true => at_seat(&game, game.next_turn()),
+            false => current,
+        };
+
+        game.remove_player(&leaving).unwrap();
+        prop_assert_eq!(at_seat(&game, game.whos_turn), expected);
+        prop_assert_eq!(game.reversed, reversed);
+    }
+
+    #[test]
+    fn new_games_turn_over_a_colored_card(seed in any::<u64>()) {
+        let game = Game::new(seed);
+        prop_assert_eq!(game.discard.0.len(), 1);
+        prop_assert_ne!(game.discard.0[0].color(), Color::None);
+        prop_assert_eq!(game.card_count(), game.supply());
+    }
+}
+
+/// A number card the player doesn't hold, if there is one.
+fn missing_card(game: &Game, player: &Uuid) -> Option<Card> {
+    let hand = &game.players[player].hand.0;
+    COLORS
+        .into_iter()
+        .flat_map(|c| (0..=9).map(move |n| Card::Number(n, c)))
+        .find(|c| !hand.contains(c))
+}
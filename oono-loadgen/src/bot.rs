@@ -0,0 +1,317 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use oono::{
+    card::{Card, Color},
+    deck::Deck,
+    event::{Error, Server, LONG_POLL_TIMEOUT_SECS},
+};
+use tokio::time::sleep;
+use uuid::Uuid;
+
+use crate::stats::Stats;
+
+/// Talks to the server over the same routes as the client, and times every request.
+#[derive(Clone)]
+pub struct Api {
+    pub http: reqwest::Client,
+    pub url: String,
+    pub stats: Arc<Mutex<Stats>>,
+}
+
+impl Api {
+    async fn call(&self, endpoint: &'static str, path: String) -> Result<Server, Error> {
+        let url = match path.is_empty() {
+            true => format!("{}/{}", self.url, endpoint),
+            false => format!("{}/{}/{}", self.url, endpoint, path),
+        };
+        let start = Instant::now();
+        let res = self.fetch(url, None).await;
+        self.stats
+            .lock()
+            .unwrap()
+            .record(endpoint, start.elapsed(), res.as_ref().err());
+        res
+    }
+
+    /// Long-polls for the next update after `since`, with the client's timeout. The time the
+    /// server held the request is kept apart from its latency.
+    async fn poll(&self, game_id: Uuid, player_id: Uuid, since: u64) -> Result<Server, Error> {
+        let url = format!(
+            "{}/RequestUpdate/{}/{}?since={}",
+            self.url, game_id, player_id, since
+        );
+        let timeout = Duration::from_secs(LONG_POLL_TIMEOUT_SECS);
+        let start = Instant::now();
+        let res = self.fetch(url, Some(timeout)).await;
+        let elapsed = start.elapsed();
+        let mut stats = self.stats.lock().unwrap();
+        match &res {
+            Ok(Server::UpdateChanged { held_ms_ret, .. }) => {
+                let held = Duration::from_millis(*held_ms_ret);
+                stats.record_held(held, true);
+                stats.record("RequestUpdate", elapsed.saturating_sub(held), None);
+            }
+            // the server doesn't say how long it held these, it answers them when it gives up
+            Ok(Server::NoChange) => stats.record_held(elapsed, false),
+            res => stats.record("RequestUpdate", elapsed, res.as_ref().err()),
+        }
+        res
+    }
+
+    /// `timeout` overrides the one of the whole run.
+    async fn fetch(&self, url: String, timeout: Option<Duration>) -> Result<Server, Error> {
+        let mut request = self.http.get(&url);
+        if let Some(t) = timeout {
+            request = request.timeout(t);
+        }
+        match request.send().await {
+            Ok(r) => match r.json::<Result<Server, Error>>().await {
+                Ok(r) => r,
+                Err(e) => Err(Error::MalformedResponse {
+                    error: e.to_string(),
+                }),
+            },
+            Err(e) => Err(Error::CouldNotContactServer {
+                url,
+                error: e.to_string(),
+            }),
+        }
+    }
+}
+
+/// How a bot's game ended.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Finished,
+    /// Nobody could move and there was nothing left to draw.
+    Stalled,
+    /// Stopped by another bot at the table, or by the end of the run.
+    Stopped,
+}
+
+/// Keeps one table busy until `deadline`, starting a new game whenever the last one ends.
+pub async fn run_table(api: Api, players: usize, retry: Duration, deadline: Instant) {
+    while Instant::now() < deadline {
+        let outcome = match play_game(&api, players, retry, deadline).await {
+            Some(o) => o,
+            None => {
+                api.stats.lock().unwrap().games_failed += 1;
+                // don't hammer a server that can't set up games
+                sleep(retry).await;
+                continue;
+            }
+        };
+        let mut stats = api.stats.lock().unwrap();
+        match outcome {
+            Outcome::Finished => stats.games_finished += 1,
+            Outcome::Stalled => stats.games_stalled += 1,
+            Outcome::Stopped => {}
+        }
+    }
+}
+
+/// Sets up one game and plays it out. `None` if it could not be set up.
+async fn play_game(
+    api: &Api,
+    players: usize,
+    retry: Duration,
+    deadline: Instant,
+) -> Option<Outcome> {
+    let (game_id, gm_token) = match api.call("CreateGame", String::new()).await {
+        Ok(Server::GameCreated {
+            game_id_ret,
+            gm_token_ret,
+            ..
+        }) => (game_id_ret, gm_token_ret),
+        _ => return None,
+    };
+
+    let mut seats = vec![];
+    for n in 0..players {
+        match api.call("JoinGame", format!("{}/bot-{}", game_id, n)).await {
+            Ok(Server::GameJoined {
+                player_id_ret,
+                order_num_ret,
+                ..
+            }) => seats.push((player_id_ret, order_num_ret)),
+            _ => return None,
+        }
+    }
+
+    // starting is based on the table as it is once everyone sat down
+    let (first, _) = seats[0];
+    let version = match api
+        .call("RequestUpdate", format!("{}/{}", game_id, first))
+        .await
+    {
+        Ok(Server::UpdateResponse { version_ret, .. }) => version_ret,
+        _ => return None,
+    };
+    match api
+        .call(
+            "StartGame",
+            format!("{}/{}?version={}", game_id, gm_token, version),
+        )
+        .await
+    {
+        Ok(Server::GameStarted) => {}
+        _ => return None,
+    }
+
+    let done = Arc::new(AtomicBool::new(false));
+    let bots: Vec<_> = seats
+        .into_iter()
+        .map(|(player_id, order_num)| {
+            let bot = Bot {
+                api: api.clone(),
+                game_id,
+                player_id,
+                order_num,
+                done: done.clone(),
+            };
+            tokio::spawn(bot.play(retry, deadline))
+        })
+        .collect();
+
+    let mut outcome = Outcome::Stopped;
+    for bot in bots {
+        match bot.await.unwrap_or(Outcome::Stopped) {
+            Outcome::Stopped => {}
+            o => outcome = o,
+        }
+    }
+    Some(outcome)
+}
+
+struct Bot {
+    api: Api,
+    game_id: Uuid,
+    player_id: Uuid,
+    order_num: usize,
+    /// Set by whichever bot sees the game end first.
+    done: Arc<AtomicBool>,
+}
+
+impl Bot {
+    /// Long-polls like the client does, and takes a turn whenever it is this bot's. Waits
+    /// `retry` after a failed request.
+    async fn play(self, retry: Duration, deadline: Instant) -> Outcome {
+        let mut seen = 0;
+        let mut latest = None;
+        let outcome = loop {
+            if self.done.load(Ordering::Relaxed) || Instant::now() >= deadline {
+                break Outcome::Stopped;
+            }
+            if let Some(Server::UpdateResponse {
+                playing_ret: true,
+                whose_turn_ret,
+                hand_ret,
+                hand_version_ret,
+                version_ret,
+                discard_ret,
+                ..
+            }) = &latest
+            {
+                if *whose_turn_ret == self.order_num {
+                    // a move changes the game, so the poll after it comes straight back
+                    match self
+                        .take_turn(hand_ret, *hand_version_ret, *version_ret, discard_ret)
+                        .await
+                    {
+                        Ok(Some(outcome)) => break outcome,
+                        Ok(None) => {}
+                        Err(_) => sleep(retry).await,
+                    }
+                }
+            }
+            match self.api.poll(self.game_id, self.player_id, seen).await {
+                Ok(Server::PlayerWon { .. }) => break Outcome::Finished,
+                Ok(Server::UpdateChanged {
+                    seen_ret, delta, ..
+                }) => {
+                    // a delta is made against the update just before it
+                    let base = match seen_ret == seen + 1 {
+                        true => latest.as_ref(),
+                        false => None,
+                    };
+                    match delta.apply(base) {
+                        Some(update) => {
+                            seen = seen_ret;
+                            latest = Some(update);
+                        }
+                        None => seen = 0,
+                    }
+                }
+                Ok(_) => {}
+                Err(_) => sleep(retry).await,
+            }
+        };
+        self.done.store(true, Ordering::Relaxed);
+        outcome
+    }
+
+    /// Plays the first legal card, or draws. `Some` once the game is over.
+    async fn take_turn(
+        &self,
+        hand: &Deck,
+        hand_version: u64,
+        version: u64,
+        discard: &Card,
+    ) -> Result<Option<Outcome>, Error> {
+        let ids = format!("{}/{}", self.game_id, self.player_id);
+        match hand.0.iter().position(|c| c.is_valid_on(discard)) {
+            Some(index) => {
+                let color = match hand.0[index] {
+                    Card::Wild(_) | Card::PlusFour(_) => favourite_color(hand),
+                    _ => Color::None,
+                };
+                let color = match color {
+                    Color::None => "None".to_string(),
+                    c => c.to_string(),
+                };
+                match self
+                    .api
+                    .call(
+                        "PlaceCard",
+                        format!(
+                            "{}/{}/{}?hand={}&version={}",
+                            ids,
+                            hand.0[index].code(),
+                            color,
+                            hand_version,
+                            version
+                        ),
+                    )
+                    .await
+                {
+                    Ok(Server::PlayerWon { .. }) => Ok(Some(Outcome::Finished)),
+                    Ok(_) => Ok(None),
+                    Err(e) => Err(e),
+                }
+            }
+            None => match self
+                .api
+                .call("DrawCard", format!("{}?version={}", ids, version))
+                .await
+            {
+                Ok(_) => Ok(None),
+                Err(Error::OutOfCards) => Ok(Some(Outcome::Stalled)),
+                Err(e) => Err(e),
+            },
+        }
+    }
+}
+
+/// The color the bot holds the most cards of.
+fn favourite_color(hand: &Deck) -> Color {
+    [Color::Red, Color::Green, Color::Yellow, Color::Blue]
+        .into_iter()
+        .max_by_key(|&c| hand.0.iter().filter(|card| card.color() == c).count())
+        .unwrap()
+}
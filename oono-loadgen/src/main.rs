@@ -0,0 +1,110 @@
+//! Plays simulated games against an oono-server through its HTTP API and reports how it held
+//! up. Every bot long-polls for updates and applies the deltas it gets, like the client.
+
+use std::{
+    process::exit,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use bot::Api;
+use stats::Stats;
+
+mod bot;
+mod stats;
+
+const USAGE: &str = "usage: oono-loadgen [--url URL] [--games N] [--players M] [--secs S] [--retry-ms MS] [--timeout-ms MS]
+
+  --url         server to test              (default http://127.0.0.1:8000)
+  --games       tables played at once       (default 10)
+  --players     bots at every table         (default 4)
+  --secs        how long to run             (default 30)
+  --retry-ms    wait after a failed request (default 500)
+  --timeout-ms  give up on a request after  (default 5000, except long polls)";
+
+struct Args {
+    url: String,
+    games: usize,
+    players: usize,
+    secs: u64,
+    retry_ms: u64,
+    timeout_ms: u64,
+}
+
+impl Args {
+    fn parse() -> Result<Self, String> {
+        let mut args = Args {
+            url: "http://127.0.0.1:8000".to_string(),
+            games: 10,
+            players: 4,
+            secs: 30,
+            retry_ms: 500,
+            timeout_ms: 5000,
+        };
+        let mut it = std::env::args().skip(1);
+        while let Some(flag) = it.next() {
+            if flag == "-h" || flag == "--help" {
+                return Err(String::new());
+            }
+            let value = it.next().ok_or_else(|| format!("{} needs a value", flag))?;
+            let number = || {
+                value
+                    .parse()
+                    .map_err(|_| format!("{} is not a number: {}", flag, value))
+            };
+            match flag.as_str() {
+                "--url" => args.url = value.trim_end_matches('/').to_string(),
+                "--games" => args.games = number()? as usize,
+                "--players" => args.players = number()? as usize,
+                "--secs" => args.secs = number()?,
+                "--retry-ms" => args.retry_ms = number()?,
+                "--timeout-ms" => args.timeout_ms = number()?,
+                _ => return Err(format!("unknown flag {}", flag)),
+            }
+        }
+        if args.games == 0 || args.players == 0 {
+            return Err("--games and --players must be at least 1".to_string());
+        }
+        Ok(args)
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let args = match Args::parse() {
+        Ok(a) => a,
+        Err(e) => {
+            if !e.is_empty() {
+                eprintln!("{}\n", e);
+            }
+            eprintln!("{}", USAGE);
+            exit(2);
+        }
+    };
+
+    let api = Api {
+        http: reqwest::ClientBuilder::new()
+            .timeout(Duration::from_millis(args.timeout_ms))
+            .build()
+            .unwrap(),
+        url: args.url.clone(),
+        stats: Arc::new(Mutex::new(Stats::default())),
+    };
+
+    println!(
+        "{} games of {} players against {} for {}s",
+        args.games, args.players, args.url, args.secs
+    );
+    let start = Instant::now();
+    let deadline = start + Duration::from_secs(args.secs);
+    let retry = Duration::from_millis(args.retry_ms);
+    let tables: Vec<_> = (0..args.games)
+        .map(|_| tokio::spawn(bot::run_table(api.clone(), args.players, retry, deadline)))
+        .collect();
+    for t in tables {
+        let _ = t.await;
+    }
+
+    let elapsed = start.elapsed();
+    println!("\n{}", api.stats.lock().unwrap().report(elapsed));
+}
@@ -0,0 +1,150 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    time::Duration,
+};
+
+use oono::event::Error;
+
+/// Everything measured during a run. Shared by every bot behind a mutex.
+#[derive(Default)]
+pub struct Stats {
+    latencies: BTreeMap<&'static str, Vec<Duration>>,
+    /// How long the server held each long poll before it answered.
+    held: Vec<Duration>,
+    /// Long polls the server gave up on without a change.
+    unchanged: usize,
+    errors: BTreeMap<String, usize>,
+    pub games_finished: usize,
+    pub games_stalled: usize,
+    pub games_failed: usize,
+}
+
+impl Stats {
+    /// Records one request to `endpoint`, and its error if it failed.
+    pub fn record(&mut self, endpoint: &'static str, latency: Duration, error: Option<&Error>) {
+        self.latencies.entry(endpoint).or_default().push(latency);
+        if let Some(e) = error {
+            *self.errors.entry(error_kind(e)).or_default() += 1;
+        }
+    }
+
+    /// Records how long the server held a long poll. Polls it gave up on without a change
+    /// have no latency to record, so they are only counted here.
+    pub fn record_held(&mut self, held: Duration, changed: bool) {
+        self.held.push(held);
+        if !changed {
+            self.unchanged += 1;
+        }
+    }
+
+    pub fn requests(&self) -> usize {
+        self.latencies.values().map(Vec::len).sum::<usize>() + self.unchanged
+    }
+
+    /// A printable summary of the run, `elapsed` is used for the request rate.
+    pub fn report(&mut self, elapsed: Duration) -> Report<'_> {
+        for l in self.latencies.values_mut() {
+            l.sort_unstable();
+        }
+        self.held.sort_unstable();
+        Report {
+            stats: self,
+            elapsed,
+        }
+    }
+}
+
+/// The variant name of `e`, without its fields.
+fn error_kind(e: &Error) -> String {
+    let debug = format!("{:?}", e);
+    debug
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Nearest-rank percentile of an already sorted list.
+fn percentile(sorted: &[Duration], p: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (sorted.len() * p).div_ceil(100);
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+pub struct Report<'a> {
+    stats: &'a Stats,
+    elapsed: Duration,
+}
+
+impl Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Stats {
+            latencies,
+            held,
+            unchanged,
+            errors,
+            games_finished,
+            games_stalled,
+            games_failed,
+        } = self.stats;
+        let requests = self.stats.requests();
+        let secs = self.elapsed.as_secs_f64();
+
+        writeln!(
+            f,
+            "requests  {} in {:.1}s ({:.1}/s)",
+            requests,
+            secs,
+            requests as f64 / secs
+        )?;
+        writeln!(
+            f,
+            "games     {} finished, {} stalled, {} failed",
+            games_finished, games_stalled, games_failed
+        )?;
+        writeln!(f)?;
+
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        writeln!(
+            f,
+            "{:<14} {:>8} {:>9} {:>9} {:>9} {:>9}",
+            "endpoint", "count", "p50 ms", "p90 ms", "p99 ms", "max ms"
+        )?;
+        let row = |f: &mut fmt::Formatter<'_>, name: &str, l: &[Duration]| {
+            writeln!(
+                f,
+                "{:<14} {:>8} {:>9.2} {:>9.2} {:>9.2} {:>9.2}",
+                name,
+                l.len(),
+                ms(percentile(l, 50)),
+                ms(percentile(l, 90)),
+                ms(percentile(l, 99)),
+                ms(l.last().copied().unwrap_or_default()),
+            )
+        };
+        let mut all: Vec<_> = latencies.values().flatten().copied().collect();
+        all.sort_unstable();
+        for (endpoint, l) in latencies.iter().chain([(&"all", &all)]) {
+            row(f, endpoint, l)?;
+        }
+
+        // time spent waiting for the game to change, which isn't latency
+        writeln!(f)?;
+        row(f, "long poll held", held)?;
+        writeln!(f, "{} long polls ended without a change", unchanged)?;
+
+        writeln!(f)?;
+        if errors.is_empty() {
+            writeln!(f, "no errors")?;
+        } else {
+            writeln!(f, "errors")?;
+            for (kind, count) in errors {
+                writeln!(f, "  {:<24} {}", kind, count)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,281 @@
+use oono::{
+    card::Card,
+    event::Error,
+    game::{Game, Player},
+};
+use rocket::{
+    http::Status,
+    request::{FromRequest, Outcome},
+    response::content::RawHtml,
+    serde::{json::Json, Serialize},
+    tokio::sync::watch,
+    Request, Route, State,
+};
+use tracing::{info, instrument, warn};
+use uuid::Uuid;
+
+use crate::{
+    codes::JoinCodes, config::OonoConfig, store::Games, telemetry::player_tag, updates::Sent,
+};
+
+/// A message from the operators, shown to every player until it is cleared.
+pub struct Announcement(watch::Sender<Option<String>>);
+
+impl Announcement {
+    pub fn current(&self) -> Option<String> {
+        self.0.borrow().clone()
+    }
+
+    pub fn set(&self, message: Option<String>) {
+        self.0.send_replace(message);
+    }
+
+    /// Wakes whenever the announcement is set or cleared from now on.
+    pub fn subscribe(&self) -> watch::Receiver<Option<String>> {
+        self.0.subscribe()
+    }
+}
+
+impl Default for Announcement {
+    fn default() -> Self {
+        Self(watch::channel(None).0)
+    }
+}
+
+/// Request guard for the admin routes. The token is sent as `Authorization: Bearer <token>`
+/// and has to match `admin_token` from the config. Without a configured token every admin
+/// request is refused.
+pub struct Admin;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Admin {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let expected = match req
+            .rocket()
+            .state::<OonoConfig>()
+            .and_then(|c| c.admin_token.as_deref())
+        {
+            Some(t) => t,
+            None => return Outcome::Failure((Status::NotFound, ())),
+        };
+        let given = req
+            .headers()
+            .get_one("Authorization")
+            .and_then(|h| h.strip_prefix("Bearer "));
+        match given {
+            Some(given) if constant_time_eq(given.as_bytes(), expected.as_bytes()) => {
+                Outcome::Success(Admin)
+            }
+            _ => {
+                warn!(path = %req.uri().path(), "rejected admin request");
+                Outcome::Failure((Status::Unauthorized, ()))
+            }
+        }
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(crate = "rocket::serde")]
+pub enum Phase {
+    Lobby,
+    Playing,
+    Finished,
+}
+
+impl Phase {
+    pub fn of(game: &Game) -> Self {
+        if game.winner().is_some() {
+            Phase::Finished
+        } else if game.started {
+            Phase::Playing
+        } else {
+            Phase::Lobby
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct GameSummary {
+    game_id: Uuid,
+    phase: Phase,
+    players: usize,
+    idle_secs: u64,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PlayerDump {
+    player_id: Uuid,
+    tag: String,
+    name: String,
+    order_num: usize,
+    hand: Vec<Card>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct GameDump {
+    game_id: Uuid,
+    phase: Phase,
+    idle_secs: u64,
+    seed: u64,
+    supply: usize,
+    creator_token: Uuid,
+    reversed: bool,
+    whos_turn: usize,
+    pot: Vec<Card>,
+    discard: Vec<Card>,
+    players: Vec<PlayerDump>,
+}
+
+impl GameDump {
+    fn new(game_id: Uuid, game: &Game) -> Self {
+        let mut players: Vec<_> = game
+            .players
+            .iter()
+            .map(
+                |(
+                    id,
+                    Player {
+                        name,
+                        order_num,
+                        hand,
+                        ..
+                    },
+                )| PlayerDump {
+                    player_id: *id,
+                    tag: player_tag(id),
+                    name: name.clone(),
+                    order_num: *order_num,
+                    hand: hand.0.clone(),
+                },
+            )
+            .collect();
+        players.sort_by_key(|p| p.order_num);
+        Self {
+            game_id,
+            phase: Phase::of(game),
+            idle_secs: game.last_activity.elapsed().as_secs(),
+            seed: game.seed,
+            supply: game.supply(),
+            creator_token: game.creator_token,
+            reversed: game.reversed,
+            whos_turn: game.whos_turn,
+            pot: game.pot.0.clone(),
+            discard: game.discard.0.clone(),
+            players,
+        }
+    }
+}
+
+#[get("/")]
+fn dashboard() -> RawHtml<&'static str> {
+    RawHtml(include_str!("admin.html"))
+}
+
+#[get("/games")]
+async fn list_games(_admin: Admin, games: &State<Games>) -> Json<Vec<GameSummary>> {
+    let mut list = games
+        .list(|game_id, game| GameSummary {
+            game_id: *game_id,
+            phase: Phase::of(game),
+            players: game.players.len(),
+            idle_secs: game.last_activity.elapsed().as_secs(),
+        })
+        .await;
+    list.sort_by_key(|g| g.idle_secs);
+    Json(list)
+}
+
+#[get("/games/<game_id>")]
+async fn dump_game(
+    _admin: Admin,
+    game_id: Uuid,
+    games: &State<Games>,
+) -> Json<Result<GameDump, Error>> {
+    match games
+        .get(&game_id, |game| GameDump::new(game_id, game))
+        .await
+    {
+        Some(dump) => Json(Ok(dump)),
+        None => Json(Err(Error::GameDoesNotExist { game_id })),
+    }
+}
+
+#[post("/games/<game_id>/end")]
+#[instrument(skip_all, fields(%game_id, action = "admin_end_game"))]
+async fn end_game(
+    _admin: Admin,
+    game_id: Uuid,
+    games: &State<Games>,
+    codes: &State<JoinCodes>,
+    sent: &State<Sent>,
+) -> Json<Result<(), Error>> {
+    match games.remove(&game_id).await {
+        Some(_) => {
+            codes.revoke(&game_id);
+            sent.forget(&game_id);
+            info!("game ended by admin");
+            Json(Ok(()))
+        }
+        None => Json(Err(Error::GameDoesNotExist { game_id })),
+    }
+}
+
+#[post("/games/<game_id>/kick/<player_id>")]
+#[instrument(skip_all, fields(%game_id, player = %player_tag(&player_id), action = "admin_kick"))]
+async fn kick_player(
+    _admin: Admin,
+    game_id: Uuid,
+    player_id: Uuid,
+    games: &State<Games>,
+) -> Json<Result<(), Error>> {
+    let kicked = games
+        .update(&game_id, |game| {
+            let player = game.remove_player(&player_id)?;
+            game.touch();
+            game.check_card_supply();
+            Some(player)
+        })
+        .await;
+    match kicked {
+        Some(Some(player)) => {
+            info!(name = %player.name, order_num = player.order_num, "player kicked by admin");
+            Json(Ok(()))
+        }
+        Some(None) => Json(Err(Error::PlayerDoesNotExist { player_id })),
+        None => Json(Err(Error::GameDoesNotExist { game_id })),
+    }
+}
+
+/// Sets the announcement shown to every player. An empty body clears it.
+#[post("/broadcast", data = "<message>")]
+#[instrument(skip_all, fields(action = "admin_broadcast"))]
+fn broadcast(_admin: Admin, message: String, announcement: &State<Announcement>) -> Json<()> {
+    let message = message.trim();
+    info!(%message, "announcement set");
+    announcement.set(if message.is_empty() {
+        None
+    } else {
+        Some(message.to_string())
+    });
+    Json(())
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![
+        dashboard,
+        list_games,
+        dump_game,
+        end_game,
+        kick_player,
+        broadcast
+    ]
+}
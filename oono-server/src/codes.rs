@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use dashmap::{mapref::entry::Entry, DashMap};
+use oono::join_code;
+use rand::thread_rng;
+use uuid::Uuid;
+
+/// The join code of every running game, kept beside the store so resolving a code doesn't have
+/// to look through every game. A finished game's removal task holds a clone, so the code is
+/// revoked in the same place the game is dropped.
+#[derive(Default, Clone)]
+pub struct JoinCodes(Arc<DashMap<String, Uuid>>);
+
+impl JoinCodes {
+    /// Issues a code for `game_id` that no other running game has.
+    pub fn issue(&self, game_id: Uuid) -> String {
+        loop {
+            let code = join_code::generate(&mut thread_rng());
+            if let Entry::Vacant(e) = self.0.entry(code.clone()) {
+                e.insert(game_id);
+                return code;
+            }
+        }
+    }
+
+    /// The game `code` belongs to. `code` must already be normalized.
+    pub fn resolve(&self, code: &str) -> Option<Uuid> {
+        self.0.get(code).map(|id| *id)
+    }
+
+    /// Frees the code of a game that has been removed.
+    pub fn revoke(&self, game_id: &Uuid) {
+        self.0.retain(|_, id| id != game_id);
+    }
+}
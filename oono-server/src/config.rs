@@ -0,0 +1,63 @@
+use rocket::{
+    figment::{
+        providers::{Env, Format, Serialized, Toml},
+        Figment,
+    },
+    serde::{Deserialize, Serialize},
+    Config,
+};
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    path::PathBuf,
+};
+
+use crate::telemetry::LogFormat;
+
+/// Oono specific settings. These live next to Rocket's own settings, so they can be set in
+/// `Rocket.toml` or through `ROCKET_` prefixed environment variables.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(crate = "rocket::serde", default)]
+pub struct OonoConfig {
+    pub log_format: LogFormat,
+    /// Bearer token for the `/admin` routes. They are disabled when this is not set.
+    pub admin_token: Option<String>,
+    /// Seconds a finished game stays around, so every player gets to see who won.
+    pub finished_game_ttl: u64,
+    /// Seconds a `/RequestUpdate?since=` is held open when nothing changes. At most 25, as
+    /// clients give up after `LONG_POLL_TIMEOUT_SECS`.
+    pub long_poll_secs: u64,
+    /// Origins whose pages may call the API from a browser, like `https://oono.example.com`.
+    /// `"*"` allows any.
+    pub cors_origins: Vec<String>,
+    /// Whether a reverse proxy in front of the server sets `X-Real-IP`. Rate limits only
+    /// trust that header when this is on, otherwise they go by the connection.
+    pub behind_proxy: bool,
+    /// A built web client, e.g. `oono-client/dist`. When set it is served from `/`.
+    pub web_client: Option<PathBuf>,
+}
+
+impl Default for OonoConfig {
+    fn default() -> Self {
+        Self {
+            log_format: LogFormat::default(),
+            admin_token: None,
+            finished_game_ttl: 60,
+            long_poll_secs: 20,
+            cors_origins: vec![],
+            behind_proxy: false,
+            web_client: None,
+        }
+    }
+}
+
+/// Rocket's default figment, except the server listens on every interface by default.
+pub fn figment() -> Figment {
+    Figment::from(Config::default())
+        .merge(Serialized::default(
+            "address",
+            IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)),
+        ))
+        .merge(Serialized::default("port", 8000))
+        .merge(Toml::file(Env::var_or("ROCKET_CONFIG", "Rocket.toml")).nested())
+        .merge(Env::prefixed("ROCKET_").ignore(&["PROFILE"]).global())
+}
@@ -0,0 +1,62 @@
+use std::io::Cursor;
+
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    http::{Header, Method, Status},
+    Request, Response,
+};
+
+/// Lets browsers on other origins call the API, e.g. a web client hosted somewhere else.
+/// Requests from origins that aren't listed get no CORS headers, so the browser blocks them.
+pub struct Cors {
+    origins: Vec<String>,
+}
+
+impl Cors {
+    /// `origins` are full origins like `https://oono.example.com`, or `*` for any.
+    pub fn new(origins: Vec<String>) -> Self {
+        Self {
+            origins: origins
+                .into_iter()
+                .map(|o| o.trim_end_matches('/').to_string())
+                .collect(),
+        }
+    }
+
+    fn allows(&self, origin: &str) -> bool {
+        self.origins.iter().any(|o| o == "*" || o == origin)
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for Cors {
+    fn info(&self) -> Info {
+        Info {
+            name: "CORS",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let origin = match req.headers().get_one("Origin") {
+            Some(o) if self.allows(o) => o,
+            _ => return,
+        };
+        res.set_header(Header::new(
+            "Access-Control-Allow-Origin",
+            origin.to_string(),
+        ));
+        res.set_header(Header::new("Vary", "Origin"));
+        // there are no OPTIONS routes, answer the preflight here instead of with a 404
+        if req.method() == Method::Options {
+            res.set_status(Status::NoContent);
+            res.set_sized_body(0, Cursor::new(""));
+            res.set_header(Header::new("Access-Control-Allow-Methods", "GET, POST"));
+            res.set_header(Header::new(
+                "Access-Control-Allow-Headers",
+                "Authorization, Content-Type",
+            ));
+            res.set_header(Header::new("Access-Control-Max-Age", "86400"));
+        }
+    }
+}
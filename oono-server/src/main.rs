@@ -1,63 +1,91 @@
 use dashmap::DashMap;
 use oono::{
-    card::{Card, Color},
-    deck::Deck,
-    event::{Error, OpaquePlayer, Server},
+    card::{Card, Color, InvalidCardCode},
+    chat::{ChatMessage, Emote, EMOTE_SECS, MAX_MESSAGE_LEN},
+    event::{Error, OpaquePlayer, Server, LONG_POLL_TIMEOUT_SECS},
     game::{Game, Player},
-    *,
+    join_code,
 };
 use rand::{thread_rng, Rng};
 use rocket::{
+    figment::Figment,
+    fs::FileServer,
     serde::json::Json,
-    tokio::{self, time::sleep},
-    Config, State,
+    tokio::{
+        self,
+        time::{sleep, timeout},
+    },
+    Build, Rocket, State,
 };
 
-use std::{collections::HashMap, mem::discriminant, net::Ipv4Addr, sync::Arc};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::{field, info, instrument, warn, Instrument, Span};
 use uuid::Uuid;
 
+use admin::{Admin, Announcement};
+use codes::JoinCodes;
+use config::OonoConfig;
+use cors::Cors;
+use store::Games;
+use telemetry::{player_tag, LogFormat};
+use throttle::{ClientAddr, Throttles};
+use updates::Sent;
+
 #[macro_use]
 extern crate rocket;
 
-#[get("/CreateGame")]
-fn create_game(
-    games: &State<Arc<DashMap<Uuid, Game>>>,
+// rocket re-exports a `uri!` helper for every route, which goes unused outside the crate root
+#[allow(unused_imports)]
+mod admin;
+mod codes;
+mod config;
+mod cors;
+mod store;
+mod telemetry;
+mod throttle;
+mod updates;
+
+#[cfg(test)]
+mod tests;
+
+/// `seed` replays a game exactly, so it is only accepted from debug builds or the admin.
+#[get("/CreateGame?<seed>")]
+// no `ret` here or in `join_game`: their responses carry the gm token and player id
+#[instrument(skip_all, fields(game_id = field::Empty, action = "create_game"))]
+async fn create_game(
+    seed: Option<u64>,
+    admin: Option<Admin>,
+    games: &State<Games>,
+    codes: &State<JoinCodes>,
 ) -> Json<Result<Server, oono::event::Error>> {
-    let mut pot = Deck::new_full();
-    let mut discard = Deck::new_empty();
-    loop {
-        discard.0.push(pot.0.pop().unwrap());
-        if discriminant(discard.0.last().unwrap()) == discriminant(&Card::Wild(Color::None))
-            || discriminant(discard.0.last().unwrap()) == discriminant(&Card::PlusFour(Color::None))
-        {
-            continue;
-        } else {
-            break;
-        }
-    }
-    let game = Game {
-        pot,
-        discard,
-        creator_token: Uuid::new_v4(),
-        reversed: false,
-        players: HashMap::new(),
-        started: false,
-        whos_turn: 0,
+    let seed = match seed {
+        Some(seed) if cfg!(debug_assertions) || admin.is_some() => seed,
+        Some(_) => return Json(Err(Error::SeedNotAllowed)),
+        None => thread_rng().gen(),
     };
+    let game = Game::new(seed);
     let id = Uuid::new_v4();
+    Span::current().record("game_id", &field::display(id));
     let token = game.creator_token;
-    games.insert(id, game);
+    games.insert(id, game).await;
+    let code = codes.issue(id);
+    info!(seed, "game created");
     Json(Ok(Server::GameCreated {
         game_id_ret: id,
         gm_token_ret: token,
+        join_code_ret: code,
     }))
 }
 
 #[get("/JoinGame/<game_id>/<name>")]
-fn join_game(
+#[instrument(skip_all, fields(%game_id, player = field::Empty, action = "join_game"))]
+async fn join_game(
     game_id: String,
     name: String,
-    games: &State<Arc<DashMap<Uuid, Game>>>,
+    games: &State<Games>,
 ) -> Json<Result<Server, Error>> {
     let game_id = match Uuid::parse_str(&game_id) {
         Ok(id) => id,
@@ -68,36 +96,64 @@ fn join_game(
             }));
         }
     };
+    join(game_id, name, games).await
+}
 
-    let mut game = match games.get_mut(&game_id) {
-        Some(game) => game,
+/// Joins by the short code from `/CreateGame` instead of the game id. Lookups are rate
+/// limited per network, and misses lock that network out for longer and longer, so codes
+/// can't be found by trying them all.
+#[get("/JoinByCode/<code>/<name>")]
+#[instrument(skip_all, fields(game_id = field::Empty, player = field::Empty, action = "join_game"))]
+async fn join_by_code(
+    code: String,
+    name: String,
+    addr: Option<ClientAddr>,
+    games: &State<Games>,
+    codes: &State<JoinCodes>,
+    throttles: &State<Throttles>,
+) -> Json<Result<Server, Error>> {
+    // only local test requests come without a peer
+    let network = addr.as_ref().map(ClientAddr::network);
+    let limited = network.is_some_and(|net| {
+        throttles.join_code_misses.is_blocked(&net) || !throttles.join_code.allow(net)
+    });
+    if limited {
+        warn!("join code lookups rate limited");
+        return Json(Err(Error::RateLimited));
+    }
+    let game_id = match join_code::normalize(&code).and_then(|c| codes.resolve(&c)) {
+        Some(id) => id,
         None => {
-            return Json(Err(Error::GameDoesNotExist { game_id }));
+            if let Some(net) = network {
+                throttles.join_code_misses.fail(net);
+            }
+            warn!("unknown join code");
+            return Json(Err(Error::InvalidJoinCode { code }));
         }
     };
+    Span::current().record("game_id", &field::display(game_id));
+    join(game_id, name, games).await
+}
 
-    let order_num = game.players.len();
-    let mut hand = vec![];
-    for _ in 0..7 {
-        if let Some(c) = game.pot.0.pop() {
-            hand.push(c);
-        } else {
-            let mut new_pot = Deck::new_full().0;
-            game.pot.0.append(&mut new_pot);
-            //this cannot fail as we just added a hundred new cards
-            hand.push(game.pot.0.pop().unwrap());
+/// Seats `name` in the game, for both ways of joining.
+async fn join(game_id: Uuid, name: String, games: &Games) -> Json<Result<Server, Error>> {
+    let joined = games
+        .update(&game_id, |game| {
+            let seated = game.add_player(name.clone())?;
+            game.touch();
+            Ok(seated)
+        })
+        .await;
+    let (player_id, order_num) = match joined {
+        Some(Ok(seated)) => seated,
+        Some(Err(e)) => return Json(Err(e)),
+        None => {
+            warn!("game does not exist");
+            return Json(Err(Error::GameDoesNotExist { game_id }));
         }
-    }
-
-    hand.sort();
-
-    let player = Player {
-        name,
-        order_num,
-        hand: Deck(hand),
     };
-    let player_id = Uuid::new_v4();
-    game.players.insert(player_id, player);
+    Span::current().record("player", &player_tag(&player_id).as_str());
+    info!(order_num, %name, "player joined");
     Json(Ok(Server::GameJoined {
         player_id_ret: player_id,
         order_num_ret: order_num,
@@ -105,11 +161,13 @@ fn join_game(
     }))
 }
 
-#[get("/StartGame/<game_id>/<gm_token>")]
-fn start_game(
+#[get("/StartGame/<game_id>/<gm_token>?<version>")]
+#[instrument(skip_all, fields(%game_id, action = "start_game", version), ret)]
+async fn start_game(
     game_id: String,
     gm_token: String,
-    games: &State<Arc<DashMap<Uuid, Game>>>,
+    version: u64,
+    games: &State<Games>,
 ) -> Json<Result<Server, Error>> {
     let game_id = match Uuid::parse_str(&game_id) {
         Ok(id) => id,
@@ -130,30 +188,86 @@ fn start_game(
         }
     };
 
-    let mut game = match games.get_mut(&game_id) {
-        Some(game) => game,
-        None => {
-            return Json(Err(Error::GameDoesNotExist { game_id }));
+    let started = games
+        .update(&game_id, |game| {
+            if gm_token != game.creator_token {
+                warn!("wrong gm token");
+                return Err(Error::InvalidGMToken {
+                    bad_token: gm_token,
+                });
+            }
+            game.check_version(version)?;
+            let whos_first = game.start()?;
+            game.touch();
+            info!(whos_first, players = game.players.len(), "game started");
+            Ok(Server::GameStarted)
+        })
+        .await;
+    Json(started.unwrap_or(Err(Error::GameDoesNotExist { game_id })))
+}
+
+/// Lets the GM turn move hints on or off for everyone in the game.
+#[get("/SetHints/<game_id>/<gm_token>/<allowed>")]
+#[instrument(skip_all, fields(%game_id, action = "set_hints", allowed), ret)]
+async fn set_hints(
+    game_id: String,
+    gm_token: String,
+    allowed: bool,
+    games: &State<Games>,
+) -> Json<Result<Server, Error>> {
+    let game_id = match Uuid::parse_str(&game_id) {
+        Ok(id) => id,
+        Err(e) => {
+            return Json(Err(Error::InvalidUuid {
+                id: game_id,
+                error: e.to_string(),
+            }));
+        }
+    };
+    let gm_token = match Uuid::parse_str(&gm_token) {
+        Ok(id) => id,
+        Err(e) => {
+            return Json(Err(Error::InvalidUuid {
+                id: gm_token,
+                error: e.to_string(),
+            }));
         }
     };
 
-    if gm_token == game.creator_token {
-        let whos_first = thread_rng().gen_range(0..game.players.len());
-        game.whos_turn = whos_first;
-        game.started = true;
-        Json(Ok(Server::GameStarted))
-    } else {
-        Json(Err(Error::InvalidGMToken {
-            bad_token: gm_token,
-        }))
-    }
+    let set = games
+        .update(&game_id, |game| {
+            if gm_token != game.creator_token {
+                warn!("wrong gm token");
+                return Err(Error::InvalidGMToken {
+                    bad_token: gm_token,
+                });
+            }
+            game.hints = allowed;
+            game.touch();
+            info!("hints set");
+            Ok(Server::HintsSet)
+        })
+        .await;
+    Json(set.unwrap_or(Err(Error::GameDoesNotExist { game_id })))
 }
 
-#[get("/RequestUpdate/<game_id>/<player_id>")]
-fn request_update(
+/// The whole update, or with `since` a long poll: held until the game changes or
+/// `long_poll_secs` pass, then answered with only what changed since that update.
+#[get("/RequestUpdate/<game_id>/<player_id>?<since>")]
+#[instrument(
+    level = "debug",
+    skip_all,
+    fields(%game_id, player = field::Empty, action = "request_update", since),
+    ret
+)]
+async fn request_update(
     game_id: String,
     player_id: String,
-    games: &State<Arc<DashMap<Uuid, Game>>>,
+    since: Option<u64>,
+    games: &State<Games>,
+    announcement: &State<Announcement>,
+    sent: &State<Sent>,
+    config: &State<OonoConfig>,
 ) -> Json<Result<Server, Error>> {
     let game_id = match Uuid::parse_str(&game_id) {
         Ok(id) => id,
@@ -173,30 +287,67 @@ fn request_update(
             }));
         }
     };
-
-    let game = match games.get_mut(&game_id) {
-        Some(game) => game,
-        None => {
-            return Json(Err(Error::GameDoesNotExist { game_id }));
+    Span::current().record("player", &player_tag(&player_id).as_str());
+
+    let held = Instant::now();
+    let base = since.and_then(|since| sent.base(game_id, player_id, since));
+    if let Some(base) = base {
+        let mut wait = Duration::from_secs(config.long_poll_secs);
+        if base.emote_shown {
+            wait = wait.min(Duration::from_secs(EMOTE_SECS));
         }
-    };
-
-    for player in game.players.values() {
-        if player.hand.0.is_empty() {
-            return Json(Ok(Server::PlayerWon {
-                order_num: player.order_num,
-            }));
+        let mut announced = announcement.subscribe();
+        // a game that is gone has nothing to wait for
+        if let Some(mut revision) = games.get(&game_id, |game| game.revision.subscribe()).await {
+            // anything from before subscribing is already in the values
+            if *revision.borrow() == base.revision && *announced.borrow() == base.announcement {
+                let _ = timeout(wait, async {
+                    tokio::select! {
+                        _ = revision.changed() => {}
+                        _ = announced.changed() => {}
+                    }
+                })
+                .await;
+            }
         }
     }
 
-    let player = if let Some(p) = game.players.get(&player_id) {
-        p
-    } else {
-        return Json(Err(Error::PlayerDoesNotExist { player_id }));
+    let announcement = announcement.current();
+    let update = games
+        .get(&game_id, |game| {
+            if let Some(order_num) = game.winner() {
+                return Ok((Server::PlayerWon { order_num }, game.revision.get()));
+            }
+            match game.players.get(&player_id) {
+                Some(player) => Ok((update_for(game, player, announcement), game.revision.get())),
+                None => Err(Error::PlayerDoesNotExist { player_id }),
+            }
+        })
+        .await;
+    let update = match update.unwrap_or(Err(Error::GameDoesNotExist { game_id })) {
+        Ok((update @ Server::UpdateResponse { .. }, revision)) => match since {
+            Some(since) => Ok(sent.answer(
+                game_id,
+                player_id,
+                since,
+                revision,
+                update,
+                held.elapsed().as_millis() as u64,
+            )),
+            None => Ok(update),
+        },
+        Ok((update, _)) => Ok(update),
+        Err(e) => Err(e),
     };
+    Json(update)
+}
 
-    Json(Ok(Server::UpdateResponse {
+/// Everything `player` gets to see of the game.
+fn update_for(game: &Game, player: &Player, announcement: Option<String>) -> Server {
+    Server::UpdateResponse {
         hand_ret: player.hand.clone(),
+        hand_version_ret: player.hand_version,
+        version_ret: game.version,
         discard_ret: *game.discard.0.last().unwrap(),
         reversed_ret: game.reversed,
         players_ret: game
@@ -209,12 +360,17 @@ fn request_update(
                         name,
                         order_num,
                         hand,
+                        emote,
+                        ..
                     },
                 )| {
                     OpaquePlayer {
                         order_num: *order_num,
                         hand_size: hand.0.len(),
                         name: name.to_string(),
+                        emote: emote
+                            .filter(|(_, at)| at.elapsed().as_secs() < EMOTE_SECS)
+                            .map(|(e, _)| e),
                     }
                 },
             )
@@ -222,19 +378,37 @@ fn request_update(
         whose_turn_ret: game.whos_turn,
         playing_ret: game.started,
         pot_size_ret: game.pot.0.len(),
-    }))
+        announcement_ret: announcement,
+        chat_ret: game.chat.0.iter().cloned().collect(),
+        moves_ret: game.moves.0.iter().cloned().collect(),
+        hints_ret: game.hints,
+    }
 }
 
-#[get("/PlaceCard/<game_id>/<player_id>/<index>/<color>")]
-fn place_card(
+#[get("/PlaceCard/<game_id>/<player_id>/<card>/<color>?<hand>&<version>")]
+// every argument is a route segment or a request guard
+#[allow(clippy::too_many_arguments)]
+#[instrument(
+    skip_all,
+    fields(%game_id, player = field::Empty, action = "place_card", %card, %color, hand, version),
+    ret
+)]
+async fn place_card(
     game_id: String,
     player_id: String,
-    index: usize,
+    card: String,
     color: String,
-    games: &State<Arc<DashMap<Uuid, Game>>>,
+    hand: u64,
+    version: u64,
+    games: &State<Games>,
+    config: &State<OonoConfig>,
+    codes: &State<JoinCodes>,
+    sent: &State<Sent>,
 ) -> Json<Result<Server, Error>> {
-    let games = games.inner().clone();
-    let games_for_dtor = games.clone();
+    let games_for_dtor = games.inner().clone();
+    let codes = codes.inner().clone();
+    let sent = sent.inner().clone();
+    let ttl = std::time::Duration::from_secs(config.finished_game_ttl);
     let game_id = match Uuid::parse_str(&game_id) {
         Ok(id) => id,
         Err(e) => {
@@ -253,151 +427,166 @@ fn place_card(
             }));
         }
     };
+    Span::current().record("player", &player_tag(&player_id).as_str());
 
-    let mut game = match games.get_mut(&game_id) {
-        Some(game) => game,
-        None => {
-            return Json(Err(Error::GameDoesNotExist { game_id }));
-        }
+    let card = match card.parse::<Card>() {
+        Ok(c) => c,
+        Err(InvalidCardCode(card)) => return Json(Err(Error::InvalidCard { card })),
     };
-    let whos_turn = game.whos_turn;
-    let discard = *game.discard.0.last().unwrap();
-
-    let player = if let Some(p) = game.players.get_mut(&player_id) {
-        p
-    } else {
-        return Json(Err(Error::PlayerDoesNotExist { player_id }));
+    let color = match color.as_str() {
+        "Red" => Some(Color::Red),
+        "Green" => Some(Color::Green),
+        "Yellow" => Some(Color::Yellow),
+        "Blue" => Some(Color::Blue),
+        _ => None,
     };
 
-    let mut card = {
-        if index > player.hand.0.len() {
-            return Json(Err(Error::CardOutOfRange { index }));
-        } else {
-            let c = player.hand.0.remove(index);
-            player.hand.0.sort();
-            c
+    let played = games
+        .update(&game_id, |game| {
+            game.check_hand_version(&player_id, hand)?;
+            game.check_version(version)?;
+            let played = game.play_card(&player_id, card, color, hand)?;
+            game.touch();
+            Ok(played)
+        })
+        .await;
+    match played.unwrap_or(Err(Error::GameDoesNotExist { game_id })) {
+        Ok(Some(order_num)) => {
+            tokio::spawn(
+                async move {
+                    sleep(ttl).await;
+                    games_for_dtor.remove(&game_id).await;
+                    codes.revoke(&game_id);
+                    sent.forget(&game_id);
+                    info!("finished game removed");
+                }
+                .instrument(Span::current()),
+            );
+            Json(Ok(Server::PlayerWon { order_num }))
         }
-    };
-
-    if !card.is_valid_on(&discard) || whos_turn != player.order_num {
-        return Json(Err(Error::IllegalMove));
-    }
-
-    if player.hand.0.is_empty() {
-        let order_num = player.order_num;
-        tokio::spawn(async move {
-            sleep(std::time::Duration::from_secs(60)).await;
-            games_for_dtor.remove(&game_id);
-        });
-        return Json(Ok(Server::PlayerWon { order_num }));
+        Ok(None) => Json(Ok(Server::CardPlaced)),
+        Err(e) => Json(Err(e)),
     }
+}
 
-    match card {
-        card::Card::Number(_, _) => {}
-        card::Card::PlusTwo(_) => {
-            let card1 = game.pop_pot();
-            let card2 = game.pop_pot();
-            let id = if let Some((id, _)) = game.players.iter().find(|p| {
-                p.1.order_num
-                    == match game.reversed {
-                        true => ((game.whos_turn + game.players.len()) - 1) % game.players.len(),
-                        false => (game.whos_turn + 1) % game.players.len(),
-                    }
-            }) {
-                *id
-            } else {
-                return Json(Err(Error::IllegalMove));
-            };
-            let next_player = if let Some(p) = game.players.get_mut(&id) {
-                p
-            } else {
-                return Json(Err(Error::IllegalMove));
-            };
-            next_player.hand.0.push(card1);
-            next_player.hand.0.push(card2);
-            next_player.hand.0.sort();
-            game.whos_turn = match game.reversed {
-                true => ((game.whos_turn + game.players.len()) - 1) % game.players.len(),
-                false => (game.whos_turn + 1) % game.players.len(),
-            };
-        }
-        card::Card::Reverse(_) => {
-            game.reversed = !game.reversed;
+#[get("/DrawCard/<game_id>/<player_id>?<version>")]
+#[instrument(
+    skip_all,
+    fields(%game_id, player = field::Empty, action = "draw_card", version),
+    ret
+)]
+async fn draw_card(
+    game_id: String,
+    player_id: String,
+    version: u64,
+    games: &State<Games>,
+) -> Json<Result<Server, Error>> {
+    let game_id = match Uuid::parse_str(&game_id) {
+        Ok(id) => id,
+        Err(e) => {
+            return Json(Err(Error::InvalidUuid {
+                id: game_id,
+                error: e.to_string(),
+            }));
         }
-        card::Card::Skip(_) => {
-            game.whos_turn = match game.reversed {
-                true => ((game.whos_turn + game.players.len()) - 1) % game.players.len(),
-                false => (game.whos_turn + 1) % game.players.len(),
-            };
+    };
+    let player_id = match Uuid::parse_str(&player_id) {
+        Ok(id) => id,
+        Err(e) => {
+            return Json(Err(Error::InvalidUuid {
+                id: player_id,
+                error: e.to_string(),
+            }));
         }
-        card::Card::Wild(_) => {
-            card = Card::Wild(match color.as_str() {
-                "Red" => Color::Red,
-                "Green" => Color::Green,
-                "Yellow" => Color::Yellow,
-                "Blue" => Color::Blue,
-                _ => {
-                    return Json(Err(Error::IllegalMove));
-                }
-            });
+    };
+    Span::current().record("player", &player_tag(&player_id).as_str());
+
+    let drawn = games
+        .update(&game_id, |game| {
+            game.check_version(version)?;
+            game.draw_card(&player_id)?;
+            game.touch();
+            Ok(Server::CardDrawn)
+        })
+        .await;
+    Json(drawn.unwrap_or(Err(Error::GameDoesNotExist { game_id })))
+}
+
+#[get("/SendChat/<game_id>/<player_id>/<message>")]
+#[instrument(skip_all, fields(%game_id, player = field::Empty, action = "send_chat"), ret)]
+async fn send_chat(
+    game_id: String,
+    player_id: String,
+    message: String,
+    games: &State<Games>,
+    throttles: &State<Throttles>,
+) -> Json<Result<Server, Error>> {
+    let game_id = match Uuid::parse_str(&game_id) {
+        Ok(id) => id,
+        Err(e) => {
+            return Json(Err(Error::InvalidUuid {
+                id: game_id,
+                error: e.to_string(),
+            }));
         }
-        card::Card::PlusFour(_) => {
-            card = Card::PlusFour(match color.as_str() {
-                "Red" => Color::Red,
-                "Green" => Color::Green,
-                "Yellow" => Color::Yellow,
-                "Blue" => Color::Blue,
-                _ => {
-                    return Json(Err(Error::IllegalMove));
-                }
-            });
-            let card1 = game.pop_pot();
-            let card2 = game.pop_pot();
-            let card3 = game.pop_pot();
-            let card4 = game.pop_pot();
-            let id = if let Some((id, _)) = game.players.iter().find(|p| {
-                p.1.order_num
-                    == match game.reversed {
-                        true => ((game.whos_turn + game.players.len()) - 1) % game.players.len(),
-                        false => (game.whos_turn + 1) % game.players.len(),
-                    }
-            }) {
-                *id
-            } else {
-                return Json(Err(Error::IllegalMove));
-            };
-            let next_player = if let Some(p) = game.players.get_mut(&id) {
-                p
-            } else {
-                return Json(Err(Error::IllegalMove));
-            };
-            next_player.hand.0.push(card1);
-            next_player.hand.0.push(card2);
-            next_player.hand.0.push(card3);
-            next_player.hand.0.push(card4);
-            next_player.hand.0.sort();
-            game.whos_turn = match game.reversed {
-                true => ((game.whos_turn + game.players.len()) - 1) % game.players.len(),
-                false => (game.whos_turn + 1) % game.players.len(),
-            };
+    };
+    let player_id = match Uuid::parse_str(&player_id) {
+        Ok(id) => id,
+        Err(e) => {
+            return Json(Err(Error::InvalidUuid {
+                id: player_id,
+                error: e.to_string(),
+            }));
         }
+    };
+    Span::current().record("player", &player_tag(&player_id).as_str());
+
+    let text = message.trim();
+    if text.is_empty() {
+        return Json(Ok(Server::ChatSent));
+    }
+    if text.chars().count() > MAX_MESSAGE_LEN {
+        return Json(Err(Error::MessageTooLong {
+            max: MAX_MESSAGE_LEN,
+        }));
     }
 
-    game.discard.0.push(card);
+    let sent = games
+        .update(&game_id, |game| {
+            let (order_num, name) = match game.players.get(&player_id) {
+                Some(p) => (p.order_num, p.name.clone()),
+                None => return Err(Error::PlayerDoesNotExist { player_id }),
+            };
 
-    game.whos_turn = match game.reversed {
-        true => ((game.whos_turn + game.players.len()) - 1) % game.players.len(),
-        false => (game.whos_turn + 1) % game.players.len(),
-    };
+            if !throttles.chat.allow(player_id) {
+                warn!("chat rate limited");
+                return Err(Error::RateLimited);
+            }
 
-    Json(Ok(Server::CardPlaced))
+            info!(order_num, message = %text, "chat message");
+            game.chat.push(ChatMessage {
+                name,
+                text: text.to_string(),
+            });
+            game.touch();
+            Ok(Server::ChatSent)
+        })
+        .await;
+    Json(sent.unwrap_or(Err(Error::GameDoesNotExist { game_id })))
 }
 
-#[get("/DrawCard/<game_id>/<player_id>")]
-fn draw_card(
+#[get("/SendEmote/<game_id>/<player_id>/<emote>")]
+#[instrument(
+    skip_all,
+    fields(%game_id, player = field::Empty, action = "send_emote", %emote),
+    ret
+)]
+async fn send_emote(
     game_id: String,
     player_id: String,
-    games: &State<Arc<DashMap<Uuid, Game>>>,
+    emote: String,
+    games: &State<Games>,
+    throttles: &State<Throttles>,
 ) -> Json<Result<Server, Error>> {
     let game_id = match Uuid::parse_str(&game_id) {
         Ok(id) => id,
@@ -417,52 +606,88 @@ fn draw_card(
             }));
         }
     };
+    Span::current().record("player", &player_tag(&player_id).as_str());
 
-    let mut game = match games.get_mut(&game_id) {
-        Some(game) => game,
-        None => {
-            return Json(Err(Error::GameDoesNotExist { game_id }));
-        }
+    let parsed = match emote.parse::<Emote>() {
+        Ok(e) => e,
+        Err(()) => return Json(Err(Error::InvalidEmote { emote })),
     };
-    let card = game.pop_pot();
-    let whos_turn = game.whos_turn;
-    let player = if let Some(p) = game.players.get_mut(&player_id) {
-        p
-    } else {
-        return Json(Err(Error::PlayerDoesNotExist { player_id }));
-    };
-
-    let hand = &mut player.hand.0;
 
-    if whos_turn == player.order_num {
-        hand.push(card);
-        hand.sort();
-    } else {
-        return Json(Err(Error::IllegalMove));
-    }
+    let sent = games
+        .update(&game_id, |game| {
+            let player = match game.players.get_mut(&player_id) {
+                Some(p) => p,
+                None => return Err(Error::PlayerDoesNotExist { player_id }),
+            };
 
-    Json(Ok(Server::CardDrawn))
+            if !throttles.emote.allow(player_id) {
+                warn!("emote rate limited");
+                return Err(Error::RateLimited);
+            }
+
+            player.emote = Some((parsed, Instant::now()));
+            info!(order_num = player.order_num, "emote sent");
+            game.touch();
+            Ok(Server::EmoteSent)
+        })
+        .await;
+    Json(sent.unwrap_or(Err(Error::GameDoesNotExist { game_id })))
 }
 
 #[launch]
 fn rocket() -> _ {
-    let config = Config {
-        address: std::net::IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)),
-        port: 8000,
-        ..Default::default()
+    let figment = config::figment();
+    telemetry::init(figment.extract_inner("log_format").unwrap_or_default());
+    build(figment, Arc::new(DashMap::<Uuid, Game>::new()))
+}
+
+/// Builds the server from `figment` around `games`, which lets the tests adjust the config
+/// and look into the store.
+fn build(figment: Figment, games: Games) -> Rocket<Build> {
+    let mut oono_config: OonoConfig = figment.extract().expect("invalid oono configuration");
+    // answer long polls while the clients are still listening
+    let max_hold = LONG_POLL_TIMEOUT_SECS - 5;
+    if oono_config.long_poll_secs > max_hold {
+        warn!(
+            long_poll_secs = oono_config.long_poll_secs,
+            max_hold, "long polls are held for less than configured"
+        );
+        oono_config.long_poll_secs = max_hold;
+    }
+    let figment = match oono_config.log_format {
+        LogFormat::Pretty => figment,
+        // rocket's own messages are forwarded too, keep escape codes out of them
+        LogFormat::Json => figment.merge(("cli_colors", false)),
     };
-    rocket::build()
-        .configure(config)
-        .manage(Arc::new(DashMap::<Uuid, Game>::new()))
+    let cors = Cors::new(oono_config.cors_origins.clone());
+    let web_client = oono_config.web_client.clone();
+    let rocket = rocket::custom(figment)
+        .attach(cors)
+        .manage(games)
+        .manage(Announcement::default())
+        .manage(Throttles::default())
+        .manage(JoinCodes::default())
+        .manage(Sent::default())
+        .manage(oono_config)
         .mount(
             "/",
             routes![
                 create_game,
                 join_game,
+                join_by_code,
                 start_game,
+                set_hints,
                 request_update,
                 place_card,
-                draw_card
+                draw_card,
+                send_chat,
+                send_emote
             ],
         )
+        .mount("/admin", admin::routes());
+    match web_client {
+        // ranked after the API routes, so no file can shadow one
+        Some(dir) => rocket.mount("/", FileServer::from(dir)),
+        None => rocket,
+    }
 }
@@ -0,0 +1,197 @@
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use oono::game::Game;
+use uuid::Uuid;
+
+/// The store every route reads its games from.
+pub type Games = Arc<dyn GameStore>;
+
+/// Somewhere to keep the running games. Routes only go through this, so the backend can be
+/// swapped without touching them.
+///
+/// The closures run while the store holds the game, so they must not call back into the
+/// store. Use `get`, `update` and `list` rather than the raw methods.
+#[rocket::async_trait]
+pub trait GameStore: Send + Sync {
+    async fn insert(&self, game_id: Uuid, game: Game);
+
+    async fn remove(&self, game_id: &Uuid) -> Option<Game>;
+
+    /// Runs `f` on the game. `false` if there is no such game.
+    async fn read(&self, game_id: &Uuid, f: &mut (dyn for<'g> FnMut(&'g Game) + Send)) -> bool;
+
+    /// Runs `f` on the game, with nobody else looking at it. `false` if there is no such game.
+    async fn write(&self, game_id: &Uuid, f: &mut (dyn for<'g> FnMut(&'g mut Game) + Send))
+        -> bool;
+
+    /// Runs `f` on every game, in no particular order.
+    async fn for_each(&self, f: &mut (dyn for<'g> FnMut(&'g Uuid, &'g Game) + Send));
+}
+
+impl dyn GameStore {
+    /// What `f` makes of the game, or `None` if there is no such game.
+    pub async fn get<R: Send>(
+        &self,
+        game_id: &Uuid,
+        f: impl FnOnce(&Game) -> R + Send,
+    ) -> Option<R> {
+        let mut f = Some(f);
+        let mut ret = None;
+        self.read(game_id, &mut |game| ret = f.take().map(|f| f(game)))
+            .await;
+        ret
+    }
+
+    /// Changes the game with `f` and returns what it returned, or `None` if there is no such
+    /// game.
+    pub async fn update<R: Send>(
+        &self,
+        game_id: &Uuid,
+        f: impl FnOnce(&mut Game) -> R + Send,
+    ) -> Option<R> {
+        let mut f = Some(f);
+        let mut ret = None;
+        self.write(game_id, &mut |game| ret = f.take().map(|f| f(game)))
+            .await;
+        ret
+    }
+
+    /// What `f` makes of every game.
+    pub async fn list<R: Send>(&self, mut f: impl FnMut(&Uuid, &Game) -> R + Send) -> Vec<R> {
+        let mut ret = vec![];
+        self.for_each(&mut |game_id, game| ret.push(f(game_id, game)))
+            .await;
+        ret
+    }
+}
+
+/// Keeps every game in memory. Each game is locked on its own, so tables don't wait on each
+/// other.
+#[rocket::async_trait]
+impl GameStore for DashMap<Uuid, Game> {
+    async fn insert(&self, game_id: Uuid, game: Game) {
+        DashMap::insert(self, game_id, game);
+    }
+
+    async fn remove(&self, game_id: &Uuid) -> Option<Game> {
+        DashMap::remove(self, game_id).map(|(_, game)| game)
+    }
+
+    async fn read(&self, game_id: &Uuid, f: &mut (dyn for<'g> FnMut(&'g Game) + Send)) -> bool {
+        match self.get(game_id) {
+            Some(game) => {
+                f(&game);
+                true
+            }
+            None => false,
+        }
+    }
+
+    async fn write(
+        &self,
+        game_id: &Uuid,
+        f: &mut (dyn for<'g> FnMut(&'g mut Game) + Send),
+    ) -> bool {
+        match self.get_mut(game_id) {
+            Some(mut game) => {
+                f(&mut game);
+                true
+            }
+            None => false,
+        }
+    }
+
+    async fn for_each(&self, f: &mut (dyn for<'g> FnMut(&'g Uuid, &'g Game) + Send)) {
+        for game in self.iter() {
+            f(game.key(), game.value());
+        }
+    }
+}
+
+#[cfg(test)]
+pub use instrumented::InstrumentedStore;
+
+#[cfg(test)]
+mod instrumented {
+    use std::{
+        cell::Cell,
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex,
+        },
+    };
+
+    use super::*;
+
+    thread_local! {
+        static IN_STORE: Cell<bool> = const { Cell::new(false) };
+    }
+
+    /// A `GameStore` for the tests. It counts how often games are locked, and panics when a
+    /// closure reaches back into the store, which would deadlock `DashMap`.
+    #[derive(Default)]
+    pub struct InstrumentedStore {
+        games: Mutex<HashMap<Uuid, Game>>,
+        pub reads: AtomicUsize,
+        pub writes: AtomicUsize,
+    }
+
+    impl InstrumentedStore {
+        fn locked<R>(&self, f: impl FnOnce(&mut HashMap<Uuid, Game>) -> R) -> R {
+            assert!(
+                !IN_STORE.with(Cell::get),
+                "the game store was used from inside one of its own closures"
+            );
+            let mut games = self.games.lock().unwrap();
+            IN_STORE.with(|s| s.set(true));
+            let ret = f(&mut games);
+            IN_STORE.with(|s| s.set(false));
+            ret
+        }
+
+        /// Changes a game directly, without counting it.
+        pub fn rig(&self, game_id: &Uuid, f: impl FnOnce(&mut Game)) {
+            self.locked(|games| f(games.get_mut(game_id).expect("no such game")));
+        }
+
+        /// Zeroes the lock counters.
+        pub fn reset(&self) {
+            self.reads.store(0, Ordering::SeqCst);
+            self.writes.store(0, Ordering::SeqCst);
+        }
+    }
+
+    #[rocket::async_trait]
+    impl GameStore for InstrumentedStore {
+        async fn insert(&self, game_id: Uuid, game: Game) {
+            self.writes.fetch_add(1, Ordering::SeqCst);
+            self.locked(|games| games.insert(game_id, game));
+        }
+
+        async fn remove(&self, game_id: &Uuid) -> Option<Game> {
+            self.writes.fetch_add(1, Ordering::SeqCst);
+            self.locked(|games| games.remove(game_id))
+        }
+
+        async fn read(&self, game_id: &Uuid, f: &mut (dyn for<'g> FnMut(&'g Game) + Send)) -> bool {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            self.locked(|games| games.get(game_id).map(f).is_some())
+        }
+
+        async fn write(
+            &self,
+            game_id: &Uuid,
+            f: &mut (dyn for<'g> FnMut(&'g mut Game) + Send),
+        ) -> bool {
+            self.writes.fetch_add(1, Ordering::SeqCst);
+            self.locked(|games| games.get_mut(game_id).map(f).is_some())
+        }
+
+        async fn for_each(&self, f: &mut (dyn for<'g> FnMut(&'g Uuid, &'g Game) + Send)) {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            self.locked(|games| games.iter().for_each(|(id, game)| f(id, game)));
+        }
+    }
+}
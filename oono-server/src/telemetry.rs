@@ -0,0 +1,36 @@
+use std::hash::Hasher;
+
+use rocket::serde::{Deserialize, Serialize};
+use siphasher::sip::SipHasher13;
+use tracing_subscriber::EnvFilter;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Pretty,
+    Json,
+}
+
+/// Installs the global subscriber. `RUST_LOG` overrides the default filter, which keeps
+/// rocket's per-request lines out since their URIs contain player ids.
+pub fn init(format: LogFormat) {
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new("info,rocket::server=warn,_=warn"));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    // a subscriber may already be installed, e.g. when several servers are built in one process
+    let _ = match format {
+        LogFormat::Pretty => builder.try_init(),
+        LogFormat::Json => builder.json().with_span_list(false).try_init(),
+    };
+}
+
+/// A player's id is also their credential, so only a hash of it ever goes into the logs. The
+/// hash is pinned down, keys and all, so a player keeps their tag across releases and restarts
+/// and logs from different versions can still be lined up.
+pub fn player_tag(player_id: &Uuid) -> String {
+    let mut hasher = SipHasher13::new_with_keys(0x6f6f_6e6f_5f70_6c61, 0x7965_725f_7461_6773);
+    hasher.write(player_id.as_bytes());
+    format!("{:016x}", hasher.finish())
+}
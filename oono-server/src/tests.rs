@@ -0,0 +1,1123 @@
+//! Drives whole games through the HTTP API with rocket's local client, no network needed.
+
+use std::{
+    net::SocketAddr,
+    sync::{atomic::Ordering, Arc},
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use oono::{
+    card::{Card, Color},
+    deck::Deck,
+    event::{Error, Server, UpdateDelta, LONG_POLL_TIMEOUT_SECS},
+    game::Game,
+    history::Move,
+};
+use rocket::{
+    http::{Header, Method, Status},
+    local::blocking::Client,
+    Build, Rocket,
+};
+use uuid::Uuid;
+
+use crate::{
+    config::{self, OonoConfig},
+    store::{Games, InstrumentedStore},
+};
+
+/// A server around `games`.
+fn server(games: Games) -> Rocket<Build> {
+    // finished games are cleaned up right away instead of after a minute, and long polls
+    // give up after a second
+    let figment = config::figment()
+        .merge(("finished_game_ttl", 0))
+        .merge(("long_poll_secs", 1))
+        .merge(("log_level", "off"));
+    super::build(figment, games)
+}
+
+/// A server around an `InstrumentedStore`, which the tests can reach through `store`.
+fn client() -> Client {
+    let store = Arc::new(InstrumentedStore::default());
+    // managed a second time under its own type, so `store` can find it
+    Client::tracked(server(store.clone()).manage(store)).expect("valid rocket instance")
+}
+
+fn store(client: &Client) -> &InstrumentedStore {
+    client.rocket().state::<Arc<InstrumentedStore>>().unwrap()
+}
+
+fn get(client: &Client, uri: &str) -> Result<Server, Error> {
+    client
+        .get(uri.to_string())
+        .dispatch()
+        .into_json()
+        .expect("every route answers with json")
+}
+
+/// Looks up a join code the way a client at `peer` would.
+fn join_from(client: &Client, code: &str, peer: SocketAddr) -> Result<Server, Error> {
+    client
+        .get(format!("/JoinByCode/{}/bob", code))
+        .remote(peer)
+        .dispatch()
+        .into_json()
+        .expect("every route answers with json")
+}
+
+struct Table {
+    game_id: Uuid,
+    gm_token: Uuid,
+    join_code: String,
+    players: Vec<Uuid>,
+}
+
+impl Table {
+    /// Creates a seeded game with `players` players, without starting it.
+    fn new(client: &Client, players: usize) -> Self {
+        let (game_id, gm_token, join_code) = match get(client, "/CreateGame?seed=1") {
+            Ok(Server::GameCreated {
+                game_id_ret,
+                gm_token_ret,
+                join_code_ret,
+            }) => (game_id_ret, gm_token_ret, join_code_ret),
+            other => panic!("could not create a game: {:?}", other),
+        };
+        let players = (0..players)
+            .map(
+                |n| match get(client, &format!("/JoinGame/{}/player{}", game_id, n)) {
+                    Ok(Server::GameJoined {
+                        player_id_ret,
+                        order_num_ret,
+                        ..
+                    }) => {
+                        assert_eq!(order_num_ret, n);
+                        player_id_ret
+                    }
+                    other => panic!("could not join: {:?}", other),
+                },
+            )
+            .collect();
+        Self {
+            game_id,
+            gm_token,
+            join_code,
+            players,
+        }
+    }
+
+    fn start(&self, client: &Client) {
+        let started = get(
+            client,
+            &format!(
+                "/StartGame/{}/{}?version={}",
+                self.game_id,
+                self.gm_token,
+                self.version(client)
+            ),
+        );
+        assert!(matches!(started, Ok(Server::GameStarted)));
+    }
+
+    /// Rigs the game state directly, so a test can set up the exact hands it needs.
+    fn rig(&self, client: &Client, f: impl FnOnce(&mut Game)) {
+        store(client).rig(&self.game_id, f);
+    }
+
+    /// Gives the player in `seat` exactly `hand` and puts `discard` on top of the discard.
+    /// The cards come out of the pot, the discard and that player's old hand. A card that
+    /// sits in someone else's hand is swapped for a spare one, so the card supply and every
+    /// other hand size stay the same.
+    fn give(&self, client: &Client, seat: usize, hand: Vec<Card>, discard: Card, turn: usize) {
+        self.rig(client, |game| {
+            let player_id = *game
+                .players
+                .iter()
+                .find(|(_, p)| p.order_num == seat)
+                .unwrap()
+                .0;
+            let mut pool = std::mem::take(&mut game.players.get_mut(&player_id).unwrap().hand.0);
+            pool.append(&mut game.pot.0);
+            pool.append(&mut game.discard.0);
+            let wanted: Vec<Card> = hand.iter().chain([&discard]).copied().collect();
+            for card in &wanted {
+                if let Some(pos) = pool.iter().position(|c| c == card) {
+                    pool.remove(pos);
+                    continue;
+                }
+                let spare = pool
+                    .iter()
+                    .position(|c| !wanted.contains(c))
+                    .expect("a spare card to swap in");
+                let spare = pool.remove(spare);
+                let holder = game
+                    .players
+                    .values_mut()
+                    .find_map(|p| p.hand.0.iter_mut().find(|c| *c == card))
+                    .expect("rigged card exists");
+                *holder = spare;
+            }
+            for p in game.players.values_mut() {
+                p.hand.0.sort();
+            }
+            game.players.get_mut(&player_id).unwrap().hand = Deck(hand);
+            game.pot = Deck(pool);
+            game.discard = Deck(vec![discard]);
+            game.whos_turn = turn;
+            game.check_card_supply();
+        });
+    }
+
+    fn update(&self, client: &Client, seat: usize) -> Result<Server, Error> {
+        get(
+            client,
+            &format!("/RequestUpdate/{}/{}", self.game_id, self.players[seat]),
+        )
+    }
+
+    fn place(
+        &self,
+        client: &Client,
+        seat: usize,
+        card: Card,
+        color: &str,
+    ) -> Result<Server, Error> {
+        get(client, &self.place_uri(client, seat, card, color))
+    }
+
+    /// The request for a move against the state `seat` sees right now.
+    fn place_uri(&self, client: &Client, seat: usize, card: Card, color: &str) -> String {
+        format!(
+            "/PlaceCard/{}/{}/{}/{}?hand={}&version={}",
+            self.game_id,
+            self.players[seat],
+            card.code(),
+            color,
+            self.hand_version(client, seat),
+            self.version(client)
+        )
+    }
+
+    /// The game version, as a client would see it.
+    fn version(&self, client: &Client) -> u64 {
+        match self.update(client, 0) {
+            Ok(Server::UpdateResponse { version_ret, .. }) => version_ret,
+            other => panic!("no update: {:?}", other),
+        }
+    }
+
+    /// The version of the hand in `seat`, as that player would see it.
+    fn hand_version(&self, client: &Client, seat: usize) -> u64 {
+        match self.update(client, seat) {
+            Ok(Server::UpdateResponse {
+                hand_version_ret, ..
+            }) => hand_version_ret,
+            other => panic!("no update: {:?}", other),
+        }
+    }
+
+    fn draw(&self, client: &Client, seat: usize) -> Result<Server, Error> {
+        get(
+            client,
+            &format!(
+                "/DrawCard/{}/{}?version={}",
+                self.game_id,
+                self.players[seat],
+                self.version(client)
+            ),
+        )
+    }
+
+    /// Hand sizes by seat, whose turn it is and the top of the discard.
+    fn state(&self, client: &Client) -> (Vec<usize>, usize, Card) {
+        match self.update(client, 0) {
+            Ok(Server::UpdateResponse {
+                players_ret,
+                whose_turn_ret,
+                discard_ret,
+                ..
+            }) => {
+                let mut sizes = vec![0; players_ret.len()];
+                for p in players_ret {
+                    sizes[p.order_num] = p.hand_size;
+                }
+                (sizes, whose_turn_ret, discard_ret)
+            }
+            other => panic!("no update: {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn create_join_and_start() {
+    let client = client();
+    let table = Table::new(&client, 3);
+
+    match table.update(&client, 1) {
+        Ok(Server::UpdateResponse {
+            playing_ret,
+            hand_ret,
+            players_ret,
+            ..
+        }) => {
+            assert!(!playing_ret);
+            assert_eq!(hand_ret.0.len(), 7);
+            assert_eq!(players_ret.len(), 3);
+        }
+        other => panic!("no update: {:?}", other),
+    }
+
+    table.start(&client);
+    assert!(matches!(
+        table.update(&client, 0),
+        Ok(Server::UpdateResponse {
+            playing_ret: true,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn wild_takes_the_chosen_color() {
+    let client = client();
+    let table = Table::new(&client, 2);
+    table.start(&client);
+    table.give(
+        &client,
+        0,
+        vec![Card::Number(3, Color::Red), Card::Wild(Color::None)],
+        Card::Number(5, Color::Green),
+        0,
+    );
+
+    // a wild without a color is refused and stays in the hand
+    assert!(matches!(
+        table.place(&client, 0, Card::Wild(Color::None), "None"),
+        Err(Error::IllegalMove)
+    ));
+    assert_eq!(table.state(&client).0[0], 2);
+
+    assert!(matches!(
+        table.place(&client, 0, Card::Wild(Color::None), "Blue"),
+        Ok(Server::CardPlaced)
+    ));
+    let (sizes, turn, discard) = table.state(&client);
+    assert_eq!(discard, Card::Wild(Color::Blue));
+    assert_eq!(sizes[0], 1);
+    assert_eq!(turn, 1);
+}
+
+#[test]
+fn plus_two_victim_draws_and_is_skipped() {
+    let client = client();
+    let table = Table::new(&client, 3);
+    table.start(&client);
+    table.give(
+        &client,
+        0,
+        vec![Card::PlusTwo(Color::Green), Card::Number(1, Color::Blue)],
+        Card::Number(4, Color::Green),
+        0,
+    );
+
+    assert!(matches!(
+        table.place(&client, 0, Card::PlusTwo(Color::Green), "None"),
+        Ok(Server::CardPlaced)
+    ));
+    let (sizes, turn, discard) = table.state(&client);
+    assert_eq!(discard, Card::PlusTwo(Color::Green));
+    assert_eq!(sizes, vec![1, 9, 7]);
+    assert_eq!(turn, 2);
+}
+
+#[test]
+fn moves_show_up_in_the_history() {
+    let client = client();
+    let table = Table::new(&client, 3);
+    table.start(&client);
+    table.give(
+        &client,
+        0,
+        vec![
+            Card::Wild(Color::None),
+            Card::PlusTwo(Color::Red),
+            Card::Number(1, Color::Blue),
+        ],
+        Card::Number(4, Color::Green),
+        0,
+    );
+
+    assert!(matches!(
+        table.place(&client, 0, Card::Wild(Color::None), "Red"),
+        Ok(Server::CardPlaced)
+    ));
+    assert!(matches!(table.draw(&client, 1), Ok(Server::CardDrawn)));
+    match table.update(&client, 2) {
+        Ok(Server::UpdateResponse { moves_ret, .. }) => assert_eq!(
+            moves_ret,
+            vec![
+                Move::Played {
+                    name: "player0".into(),
+                    card: Card::Wild(Color::Red),
+                },
+                Move::Drew {
+                    name: "player1".into()
+                },
+            ]
+        ),
+        other => panic!("no update: {:?}", other),
+    }
+
+    // back to seat 0, whose +2 hits seat 1
+    table.rig(&client, |game| game.whos_turn = 0);
+    assert!(matches!(
+        table.place(&client, 0, Card::PlusTwo(Color::Red), "None"),
+        Ok(Server::CardPlaced)
+    ));
+    match table.update(&client, 2) {
+        Ok(Server::UpdateResponse { moves_ret, .. }) => assert_eq!(
+            moves_ret[2..],
+            [
+                Move::Played {
+                    name: "player0".into(),
+                    card: Card::PlusTwo(Color::Red),
+                },
+                Move::DrewPenalty {
+                    name: "player1".into(),
+                    count: 2,
+                },
+            ]
+        ),
+        other => panic!("no update: {:?}", other),
+    }
+
+    // after a kick seat 2 moves up to seat 1, but the moves stay with who made them
+    let kicked = table.players[1];
+    table.rig(&client, |game| {
+        game.remove_player(&kicked).unwrap();
+    });
+    match table.update(&client, 0) {
+        Ok(Server::UpdateResponse { moves_ret, .. }) => assert_eq!(
+            moves_ret[1],
+            Move::Drew {
+                name: "player1".into()
+            }
+        ),
+        other => panic!("no update: {:?}", other),
+    }
+}
+
+#[test]
+fn plus_four_victim_draws_and_is_skipped() {
+    let client = client();
+    let table = Table::new(&client, 3);
+    table.start(&client);
+    table.give(
+        &client,
+        1,
+        vec![Card::Number(1, Color::Blue), Card::PlusFour(Color::None)],
+        Card::Number(4, Color::Red),
+        1,
+    );
+
+    assert!(matches!(
+        table.place(&client, 1, Card::PlusFour(Color::None), "Yellow"),
+        Ok(Server::CardPlaced)
+    ));
+    let (sizes, turn, discard) = table.state(&client);
+    assert_eq!(discard, Card::PlusFour(Color::Yellow));
+    assert_eq!(sizes, vec![7, 1, 11]);
+    assert_eq!(turn, 0);
+}
+
+#[test]
+fn reverse_with_two_players() {
+    let client = client();
+    let table = Table::new(&client, 2);
+    table.start(&client);
+    table.give(
+        &client,
+        0,
+        vec![Card::Reverse(Color::Green), Card::Number(2, Color::Green)],
+        Card::Number(7, Color::Green),
+        0,
+    );
+
+    assert!(matches!(
+        table.place(&client, 0, Card::Reverse(Color::Green), "None"),
+        Ok(Server::CardPlaced)
+    ));
+    match table.update(&client, 1) {
+        Ok(Server::UpdateResponse {
+            reversed_ret,
+            whose_turn_ret,
+            ..
+        }) => {
+            assert!(reversed_ret);
+            assert_eq!(whose_turn_ret, 1);
+        }
+        other => panic!("no update: {:?}", other),
+    }
+}
+
+#[test]
+fn reverse_changes_direction() {
+    let client = client();
+    let table = Table::new(&client, 3);
+    table.start(&client);
+    table.give(
+        &client,
+        1,
+        vec![Card::Reverse(Color::Green), Card::Number(2, Color::Green)],
+        Card::Number(7, Color::Green),
+        1,
+    );
+
+    assert!(matches!(
+        table.place(&client, 1, Card::Reverse(Color::Green), "None"),
+        Ok(Server::CardPlaced)
+    ));
+    assert_eq!(table.state(&client).1, 0);
+}
+
+#[test]
+fn illegal_moves_are_rejected() {
+    let client = client();
+    let table = Table::new(&client, 2);
+    table.start(&client);
+    table.give(
+        &client,
+        0,
+        vec![Card::Number(3, Color::Red), Card::Skip(Color::Blue)],
+        Card::Number(5, Color::Green),
+        0,
+    );
+
+    // wrong color and number
+    assert!(matches!(
+        table.place(&client, 0, Card::Number(3, Color::Red), "None"),
+        Err(Error::IllegalMove)
+    ));
+    // a card they don't hold
+    assert!(matches!(
+        table.place(&client, 0, Card::Number(5, Color::Red), "None"),
+        Err(Error::NotInHand {
+            card: Card::Number(5, Color::Red)
+        })
+    ));
+    // not a card at all
+    assert!(matches!(
+        get(
+            &client,
+            &format!(
+                "/PlaceCard/{}/{}/Number-12-Red/None?hand=0&version=0",
+                table.game_id, table.players[0]
+            )
+        ),
+        Err(Error::InvalidCard { .. })
+    ));
+    // not their turn
+    let theirs = match table.update(&client, 1) {
+        Ok(Server::UpdateResponse { hand_ret, .. }) => hand_ret.0[0],
+        other => panic!("no update: {:?}", other),
+    };
+    assert!(matches!(
+        table.place(&client, 1, theirs, "None"),
+        Err(Error::IllegalMove)
+    ));
+    assert!(matches!(table.draw(&client, 1), Err(Error::IllegalMove)));
+
+    // nothing changed hands
+    let (sizes, turn, discard) = table.state(&client);
+    assert_eq!(sizes, vec![2, 7]);
+    assert_eq!(turn, 0);
+    assert_eq!(discard, Card::Number(5, Color::Green));
+
+    assert!(matches!(table.draw(&client, 0), Ok(Server::CardDrawn)));
+    assert_eq!(table.state(&client).0, vec![3, 7]);
+}
+
+#[test]
+fn gm_can_turn_hints_off() {
+    let client = client();
+    let table = Table::new(&client, 2);
+    let hints = |seat| match table.update(&client, seat) {
+        Ok(Server::UpdateResponse { hints_ret, .. }) => hints_ret,
+        other => panic!("no update: {:?}", other),
+    };
+    assert!(hints(1));
+
+    assert!(matches!(
+        get(
+            &client,
+            &format!("/SetHints/{}/{}/false", table.game_id, Uuid::new_v4())
+        ),
+        Err(Error::InvalidGMToken { .. })
+    ));
+    assert!(hints(1));
+
+    // hints don't change what may be played, so moves made before the switch still count
+    let version = table.version(&client);
+    assert!(matches!(
+        get(
+            &client,
+            &format!("/SetHints/{}/{}/false", table.game_id, table.gm_token)
+        ),
+        Ok(Server::HintsSet)
+    ));
+    assert!(!hints(0));
+    assert!(!hints(1));
+    assert_eq!(table.version(&client), version);
+}
+
+#[test]
+fn chat_keeps_its_senders_after_a_kick() {
+    let client = client();
+    let table = Table::new(&client, 3);
+    table.start(&client);
+    for seat in [1, 2] {
+        let uri = format!("/SendChat/{}/{}/hi", table.game_id, table.players[seat]);
+        assert!(matches!(get(&client, &uri), Ok(Server::ChatSent)));
+    }
+
+    // seat 2 moves up to seat 1
+    let kicked = table.players[1];
+    table.rig(&client, |game| {
+        game.remove_player(&kicked).unwrap();
+    });
+    match table.update(&client, 0) {
+        Ok(Server::UpdateResponse { chat_ret, .. }) => assert_eq!(
+            chat_ret.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(),
+            ["player1", "player2"]
+        ),
+        other => panic!("no update: {:?}", other),
+    }
+}
+
+#[test]
+fn long_polls_only_send_what_changed() {
+    let client = client();
+    let table = Table::new(&client, 2);
+    table.start(&client);
+    let poll = |since| {
+        get(
+            &client,
+            &format!(
+                "/RequestUpdate/{}/{}?since={}",
+                table.game_id, table.players[0], since
+            ),
+        )
+    };
+
+    let everything = || UpdateDelta::full(&table.update(&client, 0).unwrap());
+
+    // the first poll gets everything
+    let seen = match poll(0) {
+        Ok(Server::UpdateChanged {
+            seen_ret, delta, ..
+        }) => {
+            assert_eq!(delta, everything());
+            seen_ret
+        }
+        other => panic!("no update: {:?}", other),
+    };
+    assert!(matches!(poll(seen), Ok(Server::NoChange)));
+
+    assert!(matches!(
+        get(
+            &client,
+            &format!("/SendChat/{}/{}/hi", table.game_id, table.players[1])
+        ),
+        Ok(Server::ChatSent)
+    ));
+    let seen = match poll(seen) {
+        Ok(Server::UpdateChanged {
+            seen_ret, delta, ..
+        }) => {
+            assert_eq!(delta.chat_ret.as_ref().map(Vec::len), Some(1));
+            let only_chat = UpdateDelta {
+                chat_ret: delta.chat_ret.clone(),
+                ..UpdateDelta::default()
+            };
+            assert_eq!(delta, only_chat);
+            seen_ret
+        }
+        other => panic!("no update: {:?}", other),
+    };
+
+    // a change this player can't see ends one wait, then polls are held again
+    assert!(matches!(
+        get(
+            &client,
+            &format!("/SetHints/{}/{}/true", table.game_id, table.gm_token)
+        ),
+        Ok(Server::HintsSet)
+    ));
+    assert!(matches!(poll(seen), Ok(Server::NoChange)));
+    let held = Instant::now();
+    assert!(matches!(poll(seen), Ok(Server::NoChange)));
+    assert!(held.elapsed() >= Duration::from_millis(900));
+
+    // a client that missed an update gets everything again
+    match poll(seen - 1) {
+        Ok(Server::UpdateChanged { delta, .. }) => assert_eq!(delta, everything()),
+        other => panic!("no update: {:?}", other),
+    }
+}
+
+#[rocket::async_test]
+async fn held_polls_wake_up_when_the_game_changes() {
+    use rocket::local::asynchronous::Client;
+
+    let figment = config::figment()
+        .merge(("long_poll_secs", 10))
+        .merge(("log_level", "off"));
+    let client = Client::tracked(super::build(
+        figment,
+        Arc::new(DashMap::<Uuid, Game>::new()),
+    ))
+    .await
+    .expect("valid rocket instance");
+    let get = |uri: String| {
+        let client = &client;
+        async move {
+            client
+                .get(uri)
+                .dispatch()
+                .await
+                .into_json::<Result<Server, Error>>()
+                .await
+                .expect("every route answers with json")
+        }
+    };
+
+    let (game_id, gm_token) = match get("/CreateGame?seed=1".to_string()).await {
+        Ok(Server::GameCreated {
+            game_id_ret,
+            gm_token_ret,
+            ..
+        }) => (game_id_ret, gm_token_ret),
+        other => panic!("could not create a game: {:?}", other),
+    };
+    let mut players = vec![];
+    for n in 0..2 {
+        match get(format!("/JoinGame/{}/player{}", game_id, n)).await {
+            Ok(Server::GameJoined { player_id_ret, .. }) => players.push(player_id_ret),
+            other => panic!("could not join: {:?}", other),
+        }
+    }
+    let update = |seat: usize| get(format!("/RequestUpdate/{}/{}", game_id, players[seat]));
+    let version = match update(0).await {
+        Ok(Server::UpdateResponse { version_ret, .. }) => version_ret,
+        other => panic!("no update: {:?}", other),
+    };
+    let started = get(format!(
+        "/StartGame/{}/{}?version={}",
+        game_id, gm_token, version
+    ));
+    assert!(matches!(started.await, Ok(Server::GameStarted)));
+    let (turn, version) = match update(0).await {
+        Ok(Server::UpdateResponse {
+            whose_turn_ret,
+            version_ret,
+            ..
+        }) => (whose_turn_ret, version_ret),
+        other => panic!("no update: {:?}", other),
+    };
+    let watcher = 1 - turn;
+    let poll = |since: u64| {
+        get(format!(
+            "/RequestUpdate/{}/{}?since={}",
+            game_id, players[watcher], since
+        ))
+    };
+    let seen = match poll(0).await {
+        Ok(Server::UpdateChanged { seen_ret, .. }) => seen_ret,
+        other => panic!("no update: {:?}", other),
+    };
+
+    // the poll is already held when the other player draws
+    let held = Instant::now();
+    let (polled, drawn) = rocket::tokio::join!(poll(seen), async {
+        rocket::tokio::time::sleep(Duration::from_millis(200)).await;
+        get(format!(
+            "/DrawCard/{}/{}?version={}",
+            game_id, players[turn], version
+        ))
+        .await
+    });
+    assert!(matches!(drawn, Ok(Server::CardDrawn)));
+    assert!(
+        held.elapsed() < Duration::from_secs(5),
+        "the poll ran into its timeout"
+    );
+    match polled {
+        Ok(Server::UpdateChanged { delta, .. }) => {
+            assert!(delta.players_ret.is_some());
+            assert!(delta.pot_size_ret.is_some());
+            assert!(delta.moves_ret.is_some());
+            // the watcher's own hand and the rest of the game stayed the same
+            assert_eq!(delta.hand_ret, None);
+            assert_eq!(delta.hand_version_ret, None);
+            assert_eq!(delta.chat_ret, None);
+            assert_eq!(delta.playing_ret, None);
+            assert_eq!(delta.hints_ret, None);
+        }
+        other => panic!("no update: {:?}", other),
+    }
+}
+
+#[test]
+fn moves_against_an_old_hand_are_rejected() {
+    let client = client();
+    let table = Table::new(&client, 2);
+    table.start(&client);
+    table.give(
+        &client,
+        0,
+        vec![Card::Number(3, Color::Red), Card::Number(8, Color::Blue)],
+        Card::Number(3, Color::Green),
+        0,
+    );
+    // the card is picked against the table as the player sees it, but a draw lands first
+    let stale = table.place_uri(&client, 0, Card::Number(3, Color::Red), "None");
+    assert!(matches!(table.draw(&client, 0), Ok(Server::CardDrawn)));
+    table.rig(&client, |game| game.whos_turn = 0);
+    assert!(matches!(get(&client, &stale), Err(Error::StaleMove)));
+    assert_eq!(table.state(&client).0[0], 3);
+
+    assert!(matches!(
+        table.place(&client, 0, Card::Number(3, Color::Red), "None"),
+        Ok(Server::CardPlaced)
+    ));
+}
+
+#[test]
+fn repeated_moves_only_apply_once() {
+    let client = client();
+    let table = Table::new(&client, 2);
+    table.start(&client);
+    table.give(
+        &client,
+        0,
+        vec![
+            Card::Number(3, Color::Red),
+            Card::Number(3, Color::Red),
+            Card::Number(3, Color::Blue),
+        ],
+        Card::Number(3, Color::Green),
+        0,
+    );
+    let version = table.version(&client);
+
+    // a double click, or a retry of a request that did get through
+    let play = format!(
+        "/PlaceCard/{}/{}/{}/None?hand={}&version={}",
+        table.game_id,
+        table.players[0],
+        Card::Number(3, Color::Red).code(),
+        table.hand_version(&client, 0),
+        version
+    );
+    assert!(matches!(get(&client, &play), Ok(Server::CardPlaced)));
+    // the hand is checked first, and it lost the card
+    assert!(matches!(get(&client, &play), Err(Error::StaleMove)));
+    let draw = format!(
+        "/DrawCard/{}/{}?version={}",
+        table.game_id, table.players[0], version
+    );
+    assert!(matches!(get(&client, &draw), Err(Error::StaleState)));
+
+    let (sizes, turn, _) = table.state(&client);
+    assert_eq!(sizes, vec![2, 7]);
+    assert_eq!(turn, 1);
+    match table.update(&client, 1) {
+        Ok(Server::UpdateResponse { version_ret, .. }) => assert!(version_ret > version),
+        other => panic!("no update: {:?}", other),
+    }
+}
+
+#[test]
+fn bad_ids_are_rejected() {
+    let client = client();
+    let table = Table::new(&client, 1);
+
+    assert!(matches!(
+        get(&client, "/JoinGame/not-a-uuid/bob"),
+        Err(Error::InvalidUuid { .. })
+    ));
+    assert!(matches!(
+        get(&client, &format!("/JoinGame/{}/bob", Uuid::new_v4())),
+        Err(Error::GameDoesNotExist { .. })
+    ));
+    assert!(matches!(
+        get(
+            &client,
+            &format!("/StartGame/{}/{}?version=1", table.game_id, Uuid::new_v4())
+        ),
+        Err(Error::InvalidGMToken { .. })
+    ));
+    assert!(matches!(
+        get(
+            &client,
+            &format!("/RequestUpdate/{}/{}", table.game_id, Uuid::new_v4())
+        ),
+        Err(Error::PlayerDoesNotExist { .. })
+    ));
+}
+
+#[test]
+fn finished_games_are_cleaned_up() {
+    let client = client();
+    let table = Table::new(&client, 2);
+    table.start(&client);
+    table.give(
+        &client,
+        1,
+        vec![Card::Number(9, Color::Yellow)],
+        Card::Number(9, Color::Blue),
+        1,
+    );
+
+    assert!(matches!(
+        table.place(&client, 1, Card::Number(9, Color::Yellow), "None"),
+        Ok(Server::PlayerWon { order_num: 1 })
+    ));
+
+    // the cleanup task runs on the client's runtime while later requests are dispatched
+    let mut cleaned_up = false;
+    for _ in 0..100 {
+        match table.update(&client, 0) {
+            Ok(Server::PlayerWon { order_num: 1 }) => {
+                std::thread::sleep(std::time::Duration::from_millis(10))
+            }
+            Err(Error::GameDoesNotExist { game_id }) => {
+                assert_eq!(game_id, table.game_id);
+                cleaned_up = true;
+                break;
+            }
+            other => panic!("unexpected update: {:?}", other),
+        }
+    }
+    assert!(cleaned_up);
+    assert!(matches!(
+        get(&client, &format!("/JoinByCode/{}/late", table.join_code)),
+        Err(Error::InvalidJoinCode { .. })
+    ));
+}
+
+#[test]
+fn join_by_code() {
+    let client = client();
+    let table = Table::new(&client, 1);
+    assert_eq!(
+        oono::join_code::normalize(&table.join_code),
+        Some(table.join_code.clone())
+    );
+
+    // codes are read out loud, so case and separators don't matter
+    let (head, tail) = table.join_code.split_at(3);
+    let typed = format!("{}-{}", head, tail).to_lowercase();
+    match get(&client, &format!("/JoinByCode/{}/bob", typed)) {
+        Ok(Server::GameJoined {
+            game_id_ret,
+            order_num_ret,
+            ..
+        }) => {
+            assert_eq!(game_id_ret, table.game_id);
+            assert_eq!(order_num_ret, 1);
+        }
+        other => panic!("could not join by code: {:?}", other),
+    }
+
+    assert!(matches!(
+        get(&client, "/JoinByCode/not-a-code/bob"),
+        Err(Error::InvalidJoinCode { .. })
+    ));
+}
+
+#[test]
+fn join_codes_cannot_be_enumerated() {
+    let client = client();
+    Table::new(&client, 1);
+    let peer = SocketAddr::from(([192, 0, 2, 1], 4000));
+
+    // 1 is never in a code, so none of these can belong to the game
+    let tries = (0..100)
+        .map(|n| join_from(&client, &format!("{:06}", n), peer))
+        .take_while(|r| matches!(r, Err(Error::InvalidJoinCode { .. })))
+        .count();
+    assert!(tries < 100, "lookups were never rate limited");
+    assert!(matches!(
+        join_from(&client, "222222", peer),
+        Err(Error::RateLimited)
+    ));
+}
+
+#[test]
+fn join_code_misses_only_lock_out_their_own_network() {
+    let client = client();
+    let table = Table::new(&client, 1);
+    let guesser = SocketAddr::from(([0x2001, 0xdb8, 0, 1, 0, 0, 0, 1], 4000));
+    while !matches!(
+        join_from(&client, "222222", guesser),
+        Err(Error::RateLimited)
+    ) {}
+
+    // another host of the same /64 is the same client
+    let neighbour = SocketAddr::from(([0x2001, 0xdb8, 0, 1, 0xffff, 0, 0, 2], 4000));
+    assert!(matches!(
+        join_from(&client, &table.join_code, neighbour),
+        Err(Error::RateLimited)
+    ));
+    for other in [
+        SocketAddr::from(([0x2001, 0xdb8, 0, 2, 0, 0, 0, 1], 4000)),
+        SocketAddr::from(([192, 0, 2, 1], 4000)),
+    ] {
+        assert!(matches!(
+            join_from(&client, "222222", other),
+            Err(Error::InvalidJoinCode { .. })
+        ));
+        assert!(matches!(
+            join_from(&client, &table.join_code, other),
+            Ok(Server::GameJoined { .. })
+        ));
+    }
+}
+
+#[test]
+fn join_code_limits_ignore_forwarded_addresses() {
+    let client = client();
+    Table::new(&client, 1);
+    let peer = SocketAddr::from(([192, 0, 2, 1], 4000));
+
+    // a new address in every header would get a new budget if the header were believed
+    let tries = (0..100)
+        .map(|n| {
+            client
+                .get(format!("/JoinByCode/{:06}/bob", n))
+                .remote(peer)
+                .header(Header::new("X-Real-IP", format!("198.51.100.{}", n)))
+                .dispatch()
+                .into_json::<Result<Server, Error>>()
+                .unwrap()
+        })
+        .take_while(|r| matches!(r, Err(Error::InvalidJoinCode { .. })))
+        .count();
+    assert_eq!(tries, 10);
+}
+
+#[test]
+fn updates_only_read_the_game() {
+    let client = client();
+    let table = Table::new(&client, 2);
+    table.start(&client);
+    table.give(
+        &client,
+        0,
+        vec![Card::Number(3, Color::Red), Card::Number(4, Color::Red)],
+        Card::Number(5, Color::Red),
+        0,
+    );
+
+    let store = store(&client);
+    store.reset();
+    table.update(&client, 0).unwrap();
+    table.update(&client, 1).unwrap();
+    assert_eq!(store.reads.load(Ordering::SeqCst), 2);
+    assert_eq!(store.writes.load(Ordering::SeqCst), 0);
+
+    // a move locks the game once
+    let play = table.place_uri(&client, 0, Card::Number(3, Color::Red), "None");
+    store.reset();
+    assert!(matches!(get(&client, &play), Ok(Server::CardPlaced)));
+    assert_eq!(store.reads.load(Ordering::SeqCst), 0);
+    assert_eq!(store.writes.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn dashmap_store_plays_a_game() {
+    let client = Client::tracked(server(Arc::new(DashMap::<Uuid, Game>::new())))
+        .expect("valid rocket instance");
+    let table = Table::new(&client, 2);
+    table.start(&client);
+    let (sizes, turn, _) = table.state(&client);
+    assert_eq!(sizes, vec![7, 7]);
+    assert!(matches!(table.draw(&client, turn), Ok(Server::CardDrawn)));
+    assert_eq!(table.state(&client).0[turn], 8);
+}
+
+/// A server with a `DashMap` store and one config value changed.
+fn configured<T: rocket::serde::Serialize>(key: &str, value: T) -> Client {
+    let figment = config::figment()
+        .merge((key, value))
+        .merge(("log_level", "off"));
+    Client::tracked(super::build(
+        figment,
+        Arc::new(DashMap::<Uuid, Game>::new()),
+    ))
+    .expect("valid rocket instance")
+}
+
+#[test]
+fn long_polls_end_before_clients_give_up() {
+    let client = configured("long_poll_secs", 60);
+    let config = client.rocket().state::<OonoConfig>().unwrap();
+    assert!(config.long_poll_secs < LONG_POLL_TIMEOUT_SECS);
+}
+
+#[test]
+fn cors_allows_only_configured_origins() {
+    let client = configured("cors_origins", ["https://play.test/"]);
+    let allowed = |origin: &'static str| {
+        client
+            .get("/CreateGame")
+            .header(Header::new("Origin", origin))
+            .dispatch()
+            .headers()
+            .get_one("Access-Control-Allow-Origin")
+            .map(str::to_string)
+    };
+    assert_eq!(
+        allowed("https://play.test").as_deref(),
+        Some("https://play.test")
+    );
+    assert_eq!(allowed("https://evil.test"), None);
+
+    let preflight = client
+        .req(Method::Options, "/admin/broadcast")
+        .header(Header::new("Origin", "https://play.test"))
+        .header(Header::new("Access-Control-Request-Method", "POST"))
+        .dispatch();
+    assert_eq!(preflight.status(), Status::NoContent);
+    assert!(preflight
+        .headers()
+        .get_one("Access-Control-Allow-Headers")
+        .is_some_and(|h| h.contains("Authorization")));
+}
+
+#[test]
+fn web_client_is_served_next_to_the_api() {
+    let dir = std::env::temp_dir().join(format!("oono-web-{}", Uuid::new_v4()));
+    std::fs::create_dir(&dir).unwrap();
+    std::fs::write(dir.join("index.html"), "<canvas id=\"the_canvas_id\">").unwrap();
+
+    let client = configured("web_client", &dir);
+    let page = client.get("/").dispatch();
+    assert_eq!(page.status(), Status::Ok);
+    assert!(page.into_string().unwrap().contains("the_canvas_id"));
+    assert!(matches!(
+        get(&client, "/CreateGame"),
+        Ok(Server::GameCreated { .. })
+    ));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn player_tags_stay_the_same_across_releases() {
+    let id = Uuid::from_u128(0x6f6f6e6f_0000_4000_8000_000000000001);
+    // changing this splits every player's log lines into before and after the upgrade
+    assert_eq!(crate::telemetry::player_tag(&id), "7b2638dc9842fcf0");
+}
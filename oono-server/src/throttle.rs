@@ -0,0 +1,189 @@
+use std::{
+    collections::VecDeque,
+    convert::Infallible,
+    hash::Hash,
+    net::{IpAddr, Ipv6Addr},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use dashmap::{mapref::one::RefMut, DashMap};
+use rocket::{
+    request::{FromRequest, Outcome},
+    Request,
+};
+use uuid::Uuid;
+
+use crate::config::OonoConfig;
+
+/// How many calls go by between sweeps for keys that have gone quiet.
+const PRUNE_EVERY: usize = 1024;
+
+/// Sliding window rate limit per player, or whatever else is keyed on: at most `max` hits
+/// within `per`.
+pub struct Throttle<K = Uuid> {
+    max: usize,
+    per: Duration,
+    hits: DashMap<K, VecDeque<Instant>>,
+    calls: AtomicUsize,
+}
+
+impl<K: Eq + Hash> Throttle<K> {
+    pub fn new(max: usize, per: Duration) -> Self {
+        Self {
+            max,
+            per,
+            hits: DashMap::new(),
+            calls: AtomicUsize::new(0),
+        }
+    }
+
+    /// Records a hit for `key`, unless that would go over the limit.
+    pub fn allow(&self, key: K) -> bool {
+        let mut hits = self.recent(key);
+        if hits.len() >= self.max {
+            false
+        } else {
+            hits.push_back(Instant::now());
+            true
+        }
+    }
+
+    /// The hits of `key` that are still within the window.
+    fn recent(&self, key: K) -> RefMut<'_, K, VecDeque<Instant>> {
+        let now = Instant::now();
+        // a sweep walks the whole map, so it only comes round once in a while
+        if self.calls.fetch_add(1, Ordering::Relaxed) % PRUNE_EVERY == PRUNE_EVERY - 1 {
+            self.hits
+                .retain(|_, h| h.back().is_some_and(|t| now.duration_since(*t) < self.per));
+        }
+        let mut hits = self.hits.entry(key).or_default();
+        while hits
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= self.per)
+        {
+            hits.pop_front();
+        }
+        hits
+    }
+}
+
+/// Lockout after repeated failures: the first `free` cost nothing, and every one after that
+/// locks the key out for twice as long as the one before, up to `max`. A key that has been
+/// quiet for `reset` starts over.
+pub struct Backoff<K> {
+    free: u32,
+    base: Duration,
+    max: Duration,
+    reset: Duration,
+    failures: DashMap<K, (u32, Instant)>,
+    calls: AtomicUsize,
+}
+
+impl<K: Eq + Hash> Backoff<K> {
+    pub fn new(free: u32, base: Duration, max: Duration, reset: Duration) -> Self {
+        Self {
+            free,
+            base,
+            max,
+            reset,
+            failures: DashMap::new(),
+            calls: AtomicUsize::new(0),
+        }
+    }
+
+    /// Records a failure for `key`.
+    pub fn fail(&self, key: K) {
+        let now = Instant::now();
+        if self.calls.fetch_add(1, Ordering::Relaxed) % PRUNE_EVERY == PRUNE_EVERY - 1 {
+            self.failures
+                .retain(|_, (_, last)| now.duration_since(*last) < self.reset);
+        }
+        let mut entry = self.failures.entry(key).or_insert((0, now));
+        let (count, last) = &mut *entry;
+        if now.duration_since(*last) >= self.reset {
+            *count = 0;
+        }
+        *count += 1;
+        *last = now;
+    }
+
+    /// Whether `key` is locked out right now.
+    pub fn is_blocked(&self, key: &K) -> bool {
+        let (count, last) = match self.failures.get(key) {
+            Some(f) => *f,
+            None => return false,
+        };
+        if count < self.free {
+            return false;
+        }
+        let doublings = (count - self.free).min(31);
+        let lockout = self.base.saturating_mul(1 << doublings).min(self.max);
+        last.elapsed() < lockout
+    }
+}
+
+/// Every rate limit the server enforces, managed as a single piece of state.
+pub struct Throttles {
+    pub chat: Throttle,
+    pub emote: Throttle,
+    /// Join code lookups per network, so codes can't be found by trying them all.
+    pub join_code: Throttle<IpAddr>,
+    /// Unknown join codes per network. A few typos are free, guessing soon slows to a crawl.
+    pub join_code_misses: Backoff<IpAddr>,
+}
+
+impl Default for Throttles {
+    fn default() -> Self {
+        Self {
+            chat: Throttle::new(5, Duration::from_secs(10)),
+            emote: Throttle::new(3, Duration::from_secs(5)),
+            join_code: Throttle::new(20, Duration::from_secs(60)),
+            join_code_misses: Backoff::new(
+                10,
+                Duration::from_secs(1),
+                Duration::from_secs(15 * 60),
+                Duration::from_secs(60 * 60),
+            ),
+        }
+    }
+}
+
+/// Request guard for the address rate limits are keyed on: the peer of the connection, or with
+/// `behind_proxy` the `X-Real-IP` header the proxy sets. Clients can send that header too, so
+/// it is ignored otherwise.
+pub struct ClientAddr(pub IpAddr);
+
+impl ClientAddr {
+    /// What limits are keyed on: the address itself for IPv4, and its /64 for IPv6, since a
+    /// single host is usually handed a whole /64 to pick addresses from.
+    pub fn network(&self) -> IpAddr {
+        match self.0 {
+            IpAddr::V4(ip) => IpAddr::V4(ip),
+            IpAddr::V6(ip) => {
+                let prefix = u128::from(ip) & !(u128::from(u64::MAX));
+                IpAddr::V6(Ipv6Addr::from(prefix))
+            }
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ClientAddr {
+    type Error = Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let behind_proxy = req
+            .rocket()
+            .state::<OonoConfig>()
+            .is_some_and(|c| c.behind_proxy);
+        let forwarded = match behind_proxy {
+            true => req.real_ip(),
+            false => None,
+        };
+        match forwarded.or_else(|| req.remote().map(|r| r.ip())) {
+            Some(ip) => Outcome::Success(ClientAddr(ip)),
+            None => Outcome::Forward(()),
+        }
+    }
+}
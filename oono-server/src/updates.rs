@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use dashmap::{mapref::entry::Entry, DashMap};
+use oono::event::{Server, UpdateDelta};
+use uuid::Uuid;
+
+/// The update every long-polling player saw last, by game and player id, so the next one only
+/// has to carry what changed. This tracks connections rather than games, so it lives outside the
+/// store; the removal task of a finished game holds a clone to drop that game's entries.
+#[derive(Default, Clone)]
+pub struct Sent(Arc<DashMap<(Uuid, Uuid), Seen>>);
+
+struct Seen {
+    /// Counts up with every update sent, the client names the one it has by this.
+    seen: u64,
+    /// `Game::revision` at the time.
+    revision: u64,
+    update: Server,
+}
+
+/// What a long poll waits on to change.
+pub struct Base {
+    pub revision: u64,
+    pub announcement: Option<String>,
+    /// Emotes go stale without the game changing, so they end the wait early.
+    pub emote_shown: bool,
+}
+
+impl Sent {
+    /// The update the player has, if `since` still names it.
+    pub fn base(&self, game_id: Uuid, player_id: Uuid, since: u64) -> Option<Base> {
+        let seen = self.0.get(&(game_id, player_id))?;
+        if seen.seen != since {
+            return None;
+        }
+        match &seen.update {
+            Server::UpdateResponse {
+                announcement_ret,
+                players_ret,
+                ..
+            } => Some(Base {
+                revision: seen.revision,
+                announcement: announcement_ret.clone(),
+                emote_shown: players_ret.iter().any(|p| p.emote.is_some()),
+            }),
+            _ => None,
+        }
+    }
+
+    /// The answer to a poll `since` an earlier update, now that the game looks like `update`.
+    /// Everything is sent again when `since` isn't the last update the player got.
+    pub fn answer(
+        &self,
+        game_id: Uuid,
+        player_id: Uuid,
+        since: u64,
+        revision: u64,
+        update: Server,
+        held_ms: u64,
+    ) -> Server {
+        let (seen, delta) = match self.0.entry((game_id, player_id)) {
+            Entry::Occupied(mut e) => {
+                let old = e.get_mut();
+                let base = (old.seen == since).then_some(&old.update);
+                let delta = UpdateDelta::between(base, &update);
+                if delta.is_empty() {
+                    // the game moved on without this player's view changing, so the next poll
+                    // has to wait for what comes after
+                    old.revision = revision;
+                    return Server::NoChange;
+                }
+                let seen = old.seen + 1;
+                e.insert(Seen {
+                    seen,
+                    revision,
+                    update,
+                });
+                (seen, delta)
+            }
+            Entry::Vacant(e) => {
+                let delta = UpdateDelta::between(None, &update);
+                e.insert(Seen {
+                    seen: 1,
+                    revision,
+                    update,
+                });
+                (1, delta)
+            }
+        };
+        Server::UpdateChanged {
+            seen_ret: seen,
+            held_ms_ret: held_ms,
+            delta,
+        }
+    }
+
+    /// Drops the updates of a game that has been removed.
+    pub fn forget(&self, game_id: &Uuid) {
+        self.0.retain(|(id, _), _| id != game_id);
+    }
+}